@@ -7,7 +7,7 @@ use std::{
 
 use image::{Rgb, RgbImage};
 
-// const TARGET: &str = "thumbv7em-none-eabihf";
+const TARGET: &str = "thumbv7em-none-eabihf";
 
 fn main() {
     println!("cargo::rerun-if-changed=assets/");
@@ -28,6 +28,11 @@ fn main() {
         );
     }
 
+    // firmware sources only build for Flipper, tests on host don't need them
+    if env::var("TARGET").unwrap() != TARGET {
+        return;
+    }
+
     let fw_path = Path::new("../../deps/flipperzero-firmware");
 
     let incs = [
@@ -0,0 +1,188 @@
+// stands in for `sys.rs` on host, where there is no firmware to call
+#![allow(non_upper_case_globals)]
+
+use core::{
+    cell::RefCell,
+    ffi::{c_char, c_void},
+    ptr::{NonNull, null},
+};
+
+use alloc::vec::Vec;
+
+pub mod furi {
+    use core::{ffi::CStr, ptr::NonNull};
+
+    use alloc::boxed::Box;
+
+    /// Fresh instance of record for every opener
+    pub struct UnsafeRecord<T> {
+        ptr: NonNull<T>,
+    }
+
+    impl<T: Default> UnsafeRecord<T> {
+        /// # Safety
+        /// Always safe, matches signature of real one
+        pub unsafe fn open(_name: &CStr) -> Self {
+            let ptr = NonNull::from(Box::leak(Box::default()));
+            Self { ptr }
+        }
+    }
+
+    impl<T> UnsafeRecord<T> {
+        pub fn as_ptr(&self) -> *mut T {
+            self.ptr.as_ptr()
+        }
+    }
+
+    impl<T> Drop for UnsafeRecord<T> {
+        fn drop(&mut self) {
+            drop(unsafe { Box::from_raw(self.ptr.as_ptr()) });
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Bt {
+    /// Arguments of every `bt_set_status_changed_callback` call
+    pub status_changed_callbacks:
+        RefCell<Vec<(BtStatusChangedCallback, *mut c_void)>>,
+}
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BtStatus(pub u8);
+
+pub const BtStatusUnavailable: BtStatus = BtStatus(0);
+pub const BtStatusOff: BtStatus = BtStatus(1);
+pub const BtStatusAdvertising: BtStatus = BtStatus(2);
+pub const BtStatusConnected: BtStatus = BtStatus(3);
+
+pub type BtStatusChangedCallback =
+    Option<unsafe extern "C" fn(status: BtStatus, context: *mut c_void)>;
+
+pub struct FuriHalBleProfileBase;
+pub struct FuriHalBleProfileTemplate;
+
+// read by firmware only
+#[allow(dead_code)]
+#[repr(C)]
+pub struct BleProfileHidParams {
+    pub device_name_prefix: *const c_char,
+    pub mac_xor: u16,
+}
+
+pub static mut ble_profile_hid: *const FuriHalBleProfileTemplate = null();
+
+pub unsafe fn bt_disconnect(_bt: *mut Bt) {}
+
+pub unsafe fn bt_forget_bonded_devices(_bt: *mut Bt) {}
+
+pub unsafe fn bt_keys_storage_set_storage_path(
+    _bt: *mut Bt,
+    _path: *const c_char,
+) {
+}
+
+pub unsafe fn bt_keys_storage_set_default_path(_bt: *mut Bt) {}
+
+pub unsafe fn bt_profile_start(
+    _bt: *mut Bt,
+    _profile_template: *const FuriHalBleProfileTemplate,
+    _params: *mut c_void,
+) -> *mut FuriHalBleProfileBase {
+    NonNull::dangling().as_ptr()
+}
+
+pub unsafe fn bt_profile_restore_default(_bt: *mut Bt) -> bool {
+    true
+}
+
+pub unsafe fn bt_set_status_changed_callback(
+    bt: *mut Bt,
+    callback: BtStatusChangedCallback,
+    context: *mut c_void,
+) {
+    let bt = unsafe { &*bt };
+    bt.status_changed_callbacks
+        .borrow_mut()
+        .push((callback, context));
+}
+
+pub unsafe fn furi_hal_bt_start_advertising() {}
+
+pub unsafe fn furi_hal_bt_stop_advertising() {}
+
+pub unsafe fn ble_profile_hid_kb_press(
+    _profile: *mut FuriHalBleProfileBase,
+    _button: u16,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_kb_release(
+    _profile: *mut FuriHalBleProfileBase,
+    _button: u16,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_kb_release_all(
+    _profile: *mut FuriHalBleProfileBase,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_consumer_key_press(
+    _profile: *mut FuriHalBleProfileBase,
+    _button: u16,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_consumer_key_release(
+    _profile: *mut FuriHalBleProfileBase,
+    _button: u16,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_consumer_key_release_all(
+    _profile: *mut FuriHalBleProfileBase,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_mouse_move(
+    _profile: *mut FuriHalBleProfileBase,
+    _dx: i8,
+    _dy: i8,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_mouse_press(
+    _profile: *mut FuriHalBleProfileBase,
+    _button: i8,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_mouse_release(
+    _profile: *mut FuriHalBleProfileBase,
+    _button: i8,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_mouse_release_all(
+    _profile: *mut FuriHalBleProfileBase,
+) -> bool {
+    true
+}
+
+pub unsafe fn ble_profile_hid_mouse_scroll(
+    _profile: *mut FuriHalBleProfileBase,
+    _delta: i8,
+) -> bool {
+    true
+}
@@ -1,34 +1,45 @@
-// pretends to be `use flipperzero_sys as sys;`
+// pretends to be `use flipperzero_sys as sys;`, on host it's a fake one
+// that tests can see calls of
+#[cfg_attr(not(target_os = "none"), path = "fake_sys.rs")]
 mod sys;
 
 use core::{
+    cell::Cell,
     ffi::{CStr, c_void},
+    fmt,
     mem::ManuallyDrop,
     ops::BitOr,
     ptr::{NonNull, null_mut},
+    str::FromStr,
 };
 
-use alloc::boxed::Box;
-use flipperzero_sys::furi::UnsafeRecord;
+use alloc::{boxed::Box, ffi::CString};
+use sys::furi::UnsafeRecord;
 
-use crate::Error;
+use crate::{Error, hid::HidSink};
 
 pub const RECORD_BT: &CStr = c"bt";
 
+/// Longest device name that's never cut short. Firmware advertises it
+/// followed by space and Flipper's own name, up to 8 characters long, and
+/// cuts everything past 17 characters.
+pub const MAX_DEVICE_NAME_LEN: usize = 8;
+
 type StatusChangedCallback<'a> = dyn Fn(BtStatus) + 'a;
 type ThinBox<T> = Box<Box<T>>;
 
-pub struct Bt<'a> {
+pub struct Bt {
     hnd: UnsafeRecord<sys::Bt>,
-    status_changed_cb: Option<ThinBox<StatusChangedCallback<'a>>>,
+    /// Whether there is live [`StatusChangedGuard`] for this record
+    status_changed_cb_set: Cell<bool>,
 }
 
-impl<'a> Bt<'a> {
+impl Bt {
     pub fn open() -> Self {
         let hnd = unsafe { UnsafeRecord::open(RECORD_BT) };
         Self {
             hnd,
-            status_changed_cb: None,
+            status_changed_cb_set: Cell::new(false),
         }
     }
 
@@ -50,10 +61,14 @@ impl<'a> Bt<'a> {
         unsafe { sys::bt_keys_storage_set_default_path(self.as_ptr()) }
     }
 
-    pub fn hid_profile_start(
-        &self,
-        mut params: sys::BleProfileHidParams,
-    ) -> Result<BleProfileBase, Error> {
+    pub fn hid_profile_start<'a>(
+        &'a self,
+        params: &'a HidProfileParams,
+    ) -> Result<BleProfileBase<'a>, Error> {
+        let mut params = sys::BleProfileHidParams {
+            device_name_prefix: params.device_name.as_ptr(),
+            mac_xor: params.mac_xor,
+        };
         let hnd = unsafe {
             sys::bt_profile_start(
                 self.as_ptr(),
@@ -84,31 +99,32 @@ impl<'a> Bt<'a> {
         unsafe { sys::bt_forget_bonded_devices(self.as_ptr()) }
     }
 
-    pub fn unset_status_changed_callback(&self) {
-        unsafe {
-            sys::bt_set_status_changed_callback(self.as_ptr(), None, null_mut())
-        }
-    }
-
-    // TODO: this causes null ptr dereference crash for some reason
-    pub fn set_status_changed_callback(&mut self, f: impl Fn(BtStatus) + 'a) {
+    /// Registers callback called on every status change.
+    ///
+    /// Callback stays registered until returned guard is dropped. Only one
+    /// callback can be registered at a time, setting another one while guard
+    /// is alive fails.
+    pub fn set_status_changed_callback<'a>(
+        &'a self,
+        f: impl Fn(BtStatus) + 'a,
+    ) -> Result<StatusChangedGuard<'a>, Error> {
         type CallbackStorage<'a> = Box<StatusChangedCallback<'a>>;
         unsafe extern "C" fn bt_status_changed_callback(
             status: sys::BtStatus,
             state: *mut c_void,
         ) {
-            let status = match status {
-                sys::BtStatusUnavailable => BtStatus::Unavailable,
-                sys::BtStatusOff => BtStatus::Off,
-                sys::BtStatusAdvertising => BtStatus::Advertising,
-                sys::BtStatusConnected => BtStatus::Connected,
-                sys::BtStatus(unknown) => BtStatus::Unknown(unknown),
-            };
+            let status = BtStatus::from_raw(status);
             let f = unsafe { &*state.cast::<CallbackStorage>() };
             f(status)
         }
-        let state = self.status_changed_cb.insert(Box::new(Box::new(f)));
-        let state_ptr: *mut CallbackStorage = &raw mut **state;
+        if self.status_changed_cb_set.replace(true) {
+            return Err(Error);
+        }
+        // firmware keeps pointer to inner box, which stays in place for as
+        // long as outer box is alive, no matter where guard gets moved
+        let mut callback: ThinBox<StatusChangedCallback> =
+            Box::new(Box::new(f));
+        let state_ptr: *mut CallbackStorage = &raw mut *callback;
         unsafe {
             sys::bt_set_status_changed_callback(
                 self.as_ptr(),
@@ -116,6 +132,84 @@ impl<'a> Bt<'a> {
                 state_ptr.cast(),
             )
         }
+        Ok(StatusChangedGuard {
+            bt: self,
+            _callback: callback,
+        })
+    }
+}
+
+/// Identity HID profile presents to hosts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HidProfileParams {
+    device_name: CString,
+    mac_xor: u16,
+}
+
+impl Default for HidProfileParams {
+    fn default() -> Self {
+        Self {
+            device_name: c"YtRemote".into(),
+            mac_xor: 1,
+        }
+    }
+}
+
+impl HidProfileParams {
+    /// Fails if `device_name` isn't valid, see
+    /// [`HidProfileParams::is_valid_device_name`]
+    pub fn new(device_name: &str) -> Result<Self, Error> {
+        if !Self::is_valid_device_name(device_name) {
+            return Err(Error);
+        }
+        let device_name = CString::new(device_name).map_err(|_| Error)?;
+        Ok(Self {
+            device_name,
+            ..Self::default()
+        })
+    }
+
+    /// Sets value Flipper's MAC address is xored with. Hosts see each value
+    /// as different device, with its own pairing, while 0 would collide
+    /// with Flipper's default profile.
+    pub fn with_mac_xor(self, mac_xor: u16) -> Self {
+        Self { mac_xor, ..self }
+    }
+
+    pub fn device_name(&self) -> &CStr {
+        &self.device_name
+    }
+
+    pub fn mac_xor(&self) -> u16 {
+        self.mac_xor
+    }
+
+    /// Whether `name` is 1 to [`MAX_DEVICE_NAME_LEN`] printable ASCII
+    /// characters
+    pub fn is_valid_device_name(name: &str) -> bool {
+        (1..=MAX_DEVICE_NAME_LEN).contains(&name.len())
+            && name.bytes().all(|b| b.is_ascii_graphic() || b == b' ')
+    }
+}
+
+/// Keeps status changed callback registered, unregistering it on drop
+#[must_use = "callback gets unregistered when guard is dropped"]
+pub struct StatusChangedGuard<'a> {
+    bt: &'a Bt,
+    _callback: ThinBox<StatusChangedCallback<'a>>,
+}
+
+impl Drop for StatusChangedGuard<'_> {
+    fn drop(&mut self) {
+        // unregister first, so firmware never sees freed callback
+        unsafe {
+            sys::bt_set_status_changed_callback(
+                self.bt.as_ptr(),
+                None,
+                null_mut(),
+            )
+        }
+        self.bt.status_changed_cb_set.set(false);
     }
 }
 
@@ -128,9 +222,31 @@ pub enum BtStatus {
     Unknown(u8),
 }
 
+impl BtStatus {
+    pub fn from_raw(status: sys::BtStatus) -> Self {
+        match status {
+            sys::BtStatusUnavailable => BtStatus::Unavailable,
+            sys::BtStatusOff => BtStatus::Off,
+            sys::BtStatusAdvertising => BtStatus::Advertising,
+            sys::BtStatusConnected => BtStatus::Connected,
+            sys::BtStatus(unknown) => BtStatus::Unknown(unknown),
+        }
+    }
+
+    pub fn into_raw(self) -> sys::BtStatus {
+        match self {
+            BtStatus::Unavailable => sys::BtStatusUnavailable,
+            BtStatus::Off => sys::BtStatusOff,
+            BtStatus::Advertising => sys::BtStatusAdvertising,
+            BtStatus::Connected => sys::BtStatusConnected,
+            BtStatus::Unknown(unknown) => sys::BtStatus(unknown),
+        }
+    }
+}
+
 pub struct BleProfileBase<'a> {
     hnd: NonNull<sys::FuriHalBleProfileBase>,
-    bt: &'a Bt<'a>,
+    bt: &'a Bt,
 }
 
 impl BleProfileBase<'_> {
@@ -149,92 +265,84 @@ impl BleProfileBase<'_> {
         let mut this = ManuallyDrop::new(self);
         unsafe { this.raw_restore_default_profile() }
     }
+}
 
-    pub fn key_press(&self, button: Key) -> Result<(), Error> {
-        let res = unsafe {
-            sys::ble_profile_hid_kb_press(self.as_ptr(), button.discriminant())
-        };
+impl HidSink for BleProfileBase<'_> {
+    fn key_press(&self, key: KeyCombo) -> Result<(), Error> {
+        let res =
+            unsafe { sys::ble_profile_hid_kb_press(self.as_ptr(), key.into()) };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn key_release(&self, button: Key) -> Result<(), Error> {
+    fn key_release(&self, key: KeyCombo) -> Result<(), Error> {
         let res = unsafe {
-            sys::ble_profile_hid_kb_release(
-                self.as_ptr(),
-                button.discriminant(),
-            )
+            sys::ble_profile_hid_kb_release(self.as_ptr(), key.into())
         };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn key_release_all(&self) -> Result<(), Error> {
+    fn key_release_all(&self) -> Result<(), Error> {
         let res = unsafe { sys::ble_profile_hid_kb_release_all(self.as_ptr()) };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn consumer_key_press(&self, button: ConsumerKey) -> Result<(), Error> {
+    fn consumer_key_press(&self, button: ConsumerKey) -> Result<(), Error> {
         let res = unsafe {
             sys::ble_profile_hid_consumer_key_press(
                 self.as_ptr(),
-                button.discriminant(),
+                button.code(),
             )
         };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn consumer_key_release(
-        &self,
-        button: ConsumerKey,
-    ) -> Result<(), Error> {
+    fn consumer_key_release(&self, button: ConsumerKey) -> Result<(), Error> {
         let res = unsafe {
             sys::ble_profile_hid_consumer_key_release(
                 self.as_ptr(),
-                button.discriminant(),
+                button.code(),
             )
         };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn consumer_key_release_all(&self) -> Result<(), Error> {
+    fn consumer_key_release_all(&self) -> Result<(), Error> {
         let res = unsafe {
             sys::ble_profile_hid_consumer_key_release_all(self.as_ptr())
         };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn mouse_press(&self, button: MouseButton) -> Result<(), Error> {
+    fn mouse_press(&self, button: MouseButton) -> Result<(), Error> {
         let res = unsafe {
-            sys::ble_profile_hid_mouse_press(
-                self.as_ptr(),
-                button.discriminant(),
-            )
+            sys::ble_profile_hid_mouse_press(self.as_ptr(), button.code() as i8)
         };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn mouse_release(&self, button: MouseButton) -> Result<(), Error> {
+    fn mouse_release(&self, button: MouseButton) -> Result<(), Error> {
         let res = unsafe {
             sys::ble_profile_hid_mouse_release(
                 self.as_ptr(),
-                button.discriminant(),
+                button.code() as i8,
             )
         };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn mouse_release_all(&self) -> Result<(), Error> {
+    fn mouse_release_all(&self) -> Result<(), Error> {
         let res =
             unsafe { sys::ble_profile_hid_mouse_release_all(self.as_ptr()) };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn mouse_move(&self, dx: i8, dy: i8) -> Result<(), Error> {
+    fn mouse_move(&self, dx: i8, dy: i8) -> Result<(), Error> {
         let res =
             unsafe { sys::ble_profile_hid_mouse_move(self.as_ptr(), dx, dy) };
         res.then_some(()).ok_or(Error)
     }
 
-    pub fn mouse_scroll(&self, delta: i8) -> Result<(), Error> {
+    fn mouse_scroll(&self, delta: i8) -> Result<(), Error> {
         let res =
             unsafe { sys::ble_profile_hid_mouse_scroll(self.as_ptr(), delta) };
         res.then_some(()).ok_or(Error)
@@ -247,7 +355,119 @@ impl Drop for BleProfileBase<'_> {
     }
 }
 
+/// Defines enum of HID usages from single page, with `Other` for usages
+/// without a name, along with conversions between variants, their names and
+/// usage IDs.
+///
+/// Usages compare by their ID, so `Other` with named usage's ID is equal to
+/// the named variant.
+macro_rules! usages {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident: $code:ty {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $value:literal,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+            Other($code),
+        }
+
+        impl $name {
+            /// All named usages, in usage ID order
+            pub const NAMES: &[(&str, $name)] =
+                &[$((stringify!($variant), $name::$variant),)*];
+
+            /// Usage ID
+            pub const fn code(self) -> $code {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Other(code) => code,
+                }
+            }
+
+            /// Usage with given ID, named variant if there is one
+            pub const fn from_code(code: $code) -> $name {
+                match code {
+                    $($value => $name::$variant,)*
+                    code => $name::Other(code),
+                }
+            }
+
+            /// Looks up usage by its variant name, ignoring ASCII case
+            pub fn from_name(name: &str) -> Option<$name> {
+                Self::NAMES
+                    .iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                    .map(|&(_, usage)| usage)
+            }
+
+            /// Variant name, `None` if usage has no name
+            pub fn name(self) -> Option<&'static str> {
+                match Self::from_code(self.code()) {
+                    $($name::$variant => Some(stringify!($variant)),)*
+                    $name::Other(_) => None,
+                }
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.code() == other.code()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl From<$name> for $code {
+            fn from(usage: $name) -> Self {
+                usage.code()
+            }
+        }
+
+        impl From<$code> for $name {
+            fn from(code: $code) -> Self {
+                Self::from_code(code)
+            }
+        }
+
+        /// Writes variant name, or usage ID in hex for unnamed ones
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self.name() {
+                    Some(name) => f.write_str(name),
+                    None => write!(f, "{:#X}", self.code()),
+                }
+            }
+        }
+
+        /// Parses variant name, ignoring ASCII case, or usage ID in hex,
+        /// like `0x2C`
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                    Some(hex) => <$code>::from_str_radix(hex, 16)
+                        .map(Self::from_code)
+                        .map_err(|_| Error),
+                    None => Self::from_name(s).ok_or(Error),
+                }
+            }
+        }
+    };
+}
+
 bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct KeyMods: u16 {
         const LeftCtrl = (1 << 8);
         const LeftShift = (1 << 9);
@@ -260,157 +480,514 @@ bitflags::bitflags! {
     }
 }
 
+impl KeyMods {
+    /// Looks up modifier by its flag name, ignoring ASCII case.
+    ///
+    /// Also accepts side-less names (`Ctrl`, `Shift`, `Alt`, `Gui`), which
+    /// mean the left one.
+    pub fn from_name_lenient(name: &str) -> Option<KeyMods> {
+        let aliases = [
+            ("Ctrl", KeyMods::LeftCtrl),
+            ("Shift", KeyMods::LeftShift),
+            ("Alt", KeyMods::LeftAlt),
+            ("Gui", KeyMods::LeftGui),
+        ];
+        Self::all()
+            .iter_names()
+            .chain(aliases)
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, mods)| mods)
+    }
+}
+
+/// Key pressed along with modifiers, as firmware takes them: usage ID in
+/// low byte, modifiers in high byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyCombo {
+    pub key: Key,
+    pub mods: KeyMods,
+}
+
+impl KeyCombo {
+    pub const fn new(key: Key, mods: KeyMods) -> Self {
+        Self { key, mods }
+    }
+}
+
+impl From<Key> for KeyCombo {
+    fn from(key: Key) -> Self {
+        Self::new(key, KeyMods::empty())
+    }
+}
+
+impl From<KeyCombo> for u16 {
+    fn from(combo: KeyCombo) -> Self {
+        combo.mods.bits() | u16::from(combo.key.code())
+    }
+}
+
+impl From<u16> for KeyCombo {
+    fn from(raw: u16) -> Self {
+        let [code, _] = raw.to_le_bytes();
+        Self::new(Key::from_code(code), KeyMods::from_bits_truncate(raw))
+    }
+}
+
 impl BitOr<KeyMods> for Key {
-    type Output = Key;
+    type Output = KeyCombo;
 
     fn bitor(self, rhs: KeyMods) -> Self::Output {
-        Key::Other(self.discriminant() | rhs.bits())
+        KeyCombo::new(self, rhs)
     }
 }
 
 impl BitOr<Key> for KeyMods {
-    type Output = Key;
+    type Output = KeyCombo;
 
     fn bitor(self, rhs: Key) -> Self::Output {
-        Key::Other(self.bits() | rhs.discriminant())
+        KeyCombo::new(rhs, self)
+    }
+}
+
+impl BitOr<KeyMods> for KeyCombo {
+    type Output = KeyCombo;
+
+    fn bitor(self, rhs: KeyMods) -> Self::Output {
+        KeyCombo::new(self.key, self.mods | rhs)
     }
 }
 
-#[repr(u16)]
-#[derive(Clone, Copy)]
-pub enum Key {
-    A = 0x04,
-    B,
-    C,
-    D,
-    E,
-    F,
-    G,
-    H,
-    I,
-    J,
-    K,
-    L,
-    M,
-    N,
-    O,
-    P,
-    Q,
-    R,
-    S,
-    T,
-    U,
-    V,
-    W,
-    X,
-    Y,
-    Z,
-    Num1,
-    Num2,
-    Num3,
-    Num4,
-    Num5,
-    Num6,
-    Num7,
-    Num8,
-    Num9,
-    Num0,
-    Enter = 0x28,
-    Escape,
-    Backspace,
-    Tab,
-    Spacebar,
-    Minus,
-    Equal,
-    LeftBracket,
-    RightBracket,
-    RightSlash,
-    Hash,
-    Semicolon,
-    Comma = 0x36,
-    Dot,
-    Slash,
-    CapsLock,
-    F1,
-    F2,
-    F3,
-    F4,
-    F5,
-    F6,
-    F7,
-    F8,
-    F9,
-    F10,
-    F11,
-    F12,
-    PrintScreen,
-    ScrollLock,
-    Pause,
-    Insert,
-    Home,
-    PageUp,
-    Delete,
-    End,
-    PageDown,
-    RightArrow,
-    LeftArrow,
-    DownArrow,
-    UpArrow,
-    VolumeUp = 0x80,
-    VolumeDown,
-    LeftCtrl = 0xE0,
-    LeftShift,
-    LeftAlt,
-    LeftGUI,
-    RightCtrl,
-    RightShift,
-    RightAlt,
-    RightGUI,
-    Other(u16),
+/// Writes modifiers and key joined with `+`, like `LeftShift+Comma`
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, _) in self.mods.iter_names() {
+            write!(f, "{name}+")?;
+        }
+        write!(f, "{}", self.key)
+    }
 }
 
-impl Key {
-    fn discriminant(&self) -> u16 {
-        match self {
-            Self::Other(other) => *other,
-            _ => unsafe { *<*const _>::from(self).cast::<u16>() },
+/// Parses modifiers and key joined with `+`, modifiers can be side-less,
+/// see [`KeyMods::from_name_lenient`]
+impl FromStr for KeyCombo {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.rsplit('+').map(str::trim);
+        let key = parts.next().unwrap_or_default().parse()?;
+        let mut mods = KeyMods::empty();
+        for part in parts {
+            mods |= KeyMods::from_name_lenient(part).ok_or(Error)?;
         }
+        Ok(Self::new(key, mods))
     }
 }
 
-#[repr(u16)]
-#[derive(Clone, Copy)]
-pub enum ConsumerKey {
-    VolumeIncrease = 0xE9,
-    VolumeDecrease = 0xEA,
-    Other(u16),
+usages! {
+    /// Usage from HID Keyboard/Keypad page (0x07)
+    ///
+    /// Firmware's keyboard report only declares usages up to
+    /// [`Key::Application`], some hosts ignore ones past it.
+    pub enum Key: u8 {
+        A = 0x04,
+        B = 0x05,
+        C = 0x06,
+        D = 0x07,
+        E = 0x08,
+        F = 0x09,
+        G = 0x0A,
+        H = 0x0B,
+        I = 0x0C,
+        J = 0x0D,
+        K = 0x0E,
+        L = 0x0F,
+        M = 0x10,
+        N = 0x11,
+        O = 0x12,
+        P = 0x13,
+        Q = 0x14,
+        R = 0x15,
+        S = 0x16,
+        T = 0x17,
+        U = 0x18,
+        V = 0x19,
+        W = 0x1A,
+        X = 0x1B,
+        Y = 0x1C,
+        Z = 0x1D,
+        Num1 = 0x1E,
+        Num2 = 0x1F,
+        Num3 = 0x20,
+        Num4 = 0x21,
+        Num5 = 0x22,
+        Num6 = 0x23,
+        Num7 = 0x24,
+        Num8 = 0x25,
+        Num9 = 0x26,
+        Num0 = 0x27,
+        Enter = 0x28,
+        Escape = 0x29,
+        Backspace = 0x2A,
+        Tab = 0x2B,
+        Spacebar = 0x2C,
+        Minus = 0x2D,
+        Equal = 0x2E,
+        LeftBracket = 0x2F,
+        RightBracket = 0x30,
+        /// `\` and `|`
+        RightSlash = 0x31,
+        /// Non-US `#` and `~`
+        Hash = 0x32,
+        Semicolon = 0x33,
+        Apostrophe = 0x34,
+        /// `` ` `` and `~`
+        Grave = 0x35,
+        Comma = 0x36,
+        Dot = 0x37,
+        Slash = 0x38,
+        CapsLock = 0x39,
+        F1 = 0x3A,
+        F2 = 0x3B,
+        F3 = 0x3C,
+        F4 = 0x3D,
+        F5 = 0x3E,
+        F6 = 0x3F,
+        F7 = 0x40,
+        F8 = 0x41,
+        F9 = 0x42,
+        F10 = 0x43,
+        F11 = 0x44,
+        F12 = 0x45,
+        PrintScreen = 0x46,
+        ScrollLock = 0x47,
+        Pause = 0x48,
+        Insert = 0x49,
+        Home = 0x4A,
+        PageUp = 0x4B,
+        Delete = 0x4C,
+        End = 0x4D,
+        PageDown = 0x4E,
+        RightArrow = 0x4F,
+        LeftArrow = 0x50,
+        DownArrow = 0x51,
+        UpArrow = 0x52,
+        KeypadNumLock = 0x53,
+        KeypadSlash = 0x54,
+        KeypadAsterisk = 0x55,
+        KeypadMinus = 0x56,
+        KeypadPlus = 0x57,
+        KeypadEnter = 0x58,
+        Keypad1 = 0x59,
+        Keypad2 = 0x5A,
+        Keypad3 = 0x5B,
+        Keypad4 = 0x5C,
+        Keypad5 = 0x5D,
+        Keypad6 = 0x5E,
+        Keypad7 = 0x5F,
+        Keypad8 = 0x60,
+        Keypad9 = 0x61,
+        Keypad0 = 0x62,
+        KeypadDot = 0x63,
+        /// Non-US `\` and `|`
+        NonUsBackslash = 0x64,
+        /// Context menu key
+        Application = 0x65,
+        Power = 0x66,
+        KeypadEqual = 0x67,
+        F13 = 0x68,
+        F14 = 0x69,
+        F15 = 0x6A,
+        F16 = 0x6B,
+        F17 = 0x6C,
+        F18 = 0x6D,
+        F19 = 0x6E,
+        F20 = 0x6F,
+        F21 = 0x70,
+        F22 = 0x71,
+        F23 = 0x72,
+        F24 = 0x73,
+        Execute = 0x74,
+        Help = 0x75,
+        Menu = 0x76,
+        Select = 0x77,
+        Stop = 0x78,
+        Again = 0x79,
+        Undo = 0x7A,
+        Cut = 0x7B,
+        Copy = 0x7C,
+        Paste = 0x7D,
+        Find = 0x7E,
+        Mute = 0x7F,
+        VolumeUp = 0x80,
+        VolumeDown = 0x81,
+        LockingCapsLock = 0x82,
+        LockingNumLock = 0x83,
+        LockingScrollLock = 0x84,
+        KeypadComma = 0x85,
+        /// Equal sign on AS/400 keyboards
+        KeypadEqualSign = 0x86,
+        International1 = 0x87,
+        International2 = 0x88,
+        International3 = 0x89,
+        International4 = 0x8A,
+        International5 = 0x8B,
+        International6 = 0x8C,
+        International7 = 0x8D,
+        International8 = 0x8E,
+        International9 = 0x8F,
+        Lang1 = 0x90,
+        Lang2 = 0x91,
+        Lang3 = 0x92,
+        Lang4 = 0x93,
+        Lang5 = 0x94,
+        Lang6 = 0x95,
+        Lang7 = 0x96,
+        Lang8 = 0x97,
+        Lang9 = 0x98,
+        AlternateErase = 0x99,
+        SysReq = 0x9A,
+        Cancel = 0x9B,
+        Clear = 0x9C,
+        Prior = 0x9D,
+        Return = 0x9E,
+        Separator = 0x9F,
+        Out = 0xA0,
+        Oper = 0xA1,
+        ClearAgain = 0xA2,
+        CrSel = 0xA3,
+        ExSel = 0xA4,
+        Keypad00 = 0xB0,
+        Keypad000 = 0xB1,
+        ThousandsSeparator = 0xB2,
+        DecimalSeparator = 0xB3,
+        CurrencyUnit = 0xB4,
+        CurrencySubunit = 0xB5,
+        KeypadLeftParen = 0xB6,
+        KeypadRightParen = 0xB7,
+        KeypadLeftBrace = 0xB8,
+        KeypadRightBrace = 0xB9,
+        KeypadTab = 0xBA,
+        KeypadBackspace = 0xBB,
+        KeypadA = 0xBC,
+        KeypadB = 0xBD,
+        KeypadC = 0xBE,
+        KeypadD = 0xBF,
+        KeypadE = 0xC0,
+        KeypadF = 0xC1,
+        KeypadXor = 0xC2,
+        KeypadCaret = 0xC3,
+        KeypadPercent = 0xC4,
+        KeypadLess = 0xC5,
+        KeypadGreater = 0xC6,
+        KeypadAmpersand = 0xC7,
+        KeypadDoubleAmpersand = 0xC8,
+        KeypadPipe = 0xC9,
+        KeypadDoublePipe = 0xCA,
+        KeypadColon = 0xCB,
+        KeypadHash = 0xCC,
+        KeypadSpace = 0xCD,
+        KeypadAt = 0xCE,
+        KeypadBang = 0xCF,
+        KeypadMemoryStore = 0xD0,
+        KeypadMemoryRecall = 0xD1,
+        KeypadMemoryClear = 0xD2,
+        KeypadMemoryAdd = 0xD3,
+        KeypadMemorySubtract = 0xD4,
+        KeypadMemoryMultiply = 0xD5,
+        KeypadMemoryDivide = 0xD6,
+        KeypadPlusMinus = 0xD7,
+        KeypadClear = 0xD8,
+        KeypadClearEntry = 0xD9,
+        KeypadBinary = 0xDA,
+        KeypadOctal = 0xDB,
+        KeypadDecimal = 0xDC,
+        KeypadHexadecimal = 0xDD,
+        LeftCtrl = 0xE0,
+        LeftShift = 0xE1,
+        LeftAlt = 0xE2,
+        LeftGUI = 0xE3,
+        RightCtrl = 0xE4,
+        RightShift = 0xE5,
+        RightAlt = 0xE6,
+        RightGUI = 0xE7,
+    }
 }
 
-impl ConsumerKey {
-    fn discriminant(&self) -> u16 {
-        match self {
-            Self::Other(other) => *other,
-            _ => unsafe { *<*const _>::from(self).cast::<u16>() },
-        }
+usages! {
+    /// Usage from HID Consumer page (0x0C)
+    pub enum ConsumerKey: u16 {
+        Power = 0x30,
+        Sleep = 0x32,
+        Menu = 0x40,
+        ClosedCaption = 0x61,
+        BrightnessIncrement = 0x6F,
+        BrightnessDecrement = 0x70,
+        ChannelIncrement = 0x9C,
+        ChannelDecrement = 0x9D,
+        Play = 0xB0,
+        Pause = 0xB1,
+        Record = 0xB2,
+        FastForward = 0xB3,
+        Rewind = 0xB4,
+        ScanNextTrack = 0xB5,
+        ScanPreviousTrack = 0xB6,
+        Stop = 0xB7,
+        Eject = 0xB8,
+        RandomPlay = 0xB9,
+        Repeat = 0xBC,
+        StopEject = 0xCC,
+        PlayPause = 0xCD,
+        Mute = 0xE2,
+        BassBoost = 0xE5,
+        VolumeIncrease = 0xE9,
+        VolumeDecrease = 0xEA,
+        /// Launches media player
+        AlConsumerControlConfiguration = 0x183,
+        AlEmailReader = 0x18A,
+        AlCalculator = 0x192,
+        /// Opens file browser, "My Computer"
+        AlLocalMachineBrowser = 0x194,
+        AlInternetBrowser = 0x196,
+        /// Opens search
+        AcSearch = 0x221,
+        AcHome = 0x223,
+        /// Browser back
+        AcBack = 0x224,
+        AcForward = 0x225,
+        AcStop = 0x226,
+        AcRefresh = 0x227,
+        AcBookmarks = 0x22A,
     }
 }
 
-#[repr(i8)]
-#[derive(Clone, Copy)]
-pub enum MouseButton {
-    M1 = 0x01,
-    M2 = 0x02,
-    M3 = 0x03,
-    M4 = 0x04,
-    M5 = 0x05,
-    Other(i8),
+usages! {
+    /// Mouse button, as bit in buttons report
+    pub enum MouseButton: u8 {
+        M1 = 0x01,
+        M2 = 0x02,
+        M3 = 0x04,
+        M4 = 0x08,
+        M5 = 0x10,
+    }
 }
 
-impl MouseButton {
-    fn discriminant(&self) -> i8 {
-        match self {
-            Self::Other(other) => *other,
-            _ => unsafe { *<*const _>::from(self).cast::<i8>() },
-        }
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, vec::Vec};
+
+    use super::*;
+
+    /// Calls made to fake firmware so far
+    fn status_changed_calls(
+        bt: &Bt,
+    ) -> Vec<(sys::BtStatusChangedCallback, *mut c_void)> {
+        unsafe { &*bt.as_ptr() }.status_changed_callbacks.take()
+    }
+
+    #[test]
+    fn status_changed_callback_gets_called() {
+        let bt = Bt::open();
+        let seen = Rc::new(Cell::new(None));
+        let guard = bt
+            .set_status_changed_callback({
+                let seen = seen.clone();
+                move |status| seen.set(Some(status))
+            })
+            .unwrap();
+        let calls = status_changed_calls(&bt);
+        let [(Some(callback), context)] = calls[..] else {
+            panic!("callback not registered");
+        };
+        assert!(!context.is_null());
+        unsafe { callback(sys::BtStatusConnected, context) };
+        assert!(seen.get() == Some(BtStatus::Connected));
+        unsafe { callback(sys::BtStatus(42), context) };
+        assert!(seen.get() == Some(BtStatus::Unknown(42)));
+        drop(guard);
+    }
+
+    #[test]
+    fn dropping_guard_unregisters_callback() {
+        let bt = Bt::open();
+        let guard = bt.set_status_changed_callback(|_| ()).unwrap();
+        status_changed_calls(&bt);
+        drop(guard);
+        let calls = status_changed_calls(&bt);
+        let [(None, context)] = calls[..] else {
+            panic!("callback not unregistered");
+        };
+        assert!(context.is_null());
+    }
+
+    #[test]
+    fn only_one_status_changed_callback_at_a_time() {
+        let bt = Bt::open();
+        let guard = bt.set_status_changed_callback(|_| ()).unwrap();
+        status_changed_calls(&bt);
+        assert!(bt.set_status_changed_callback(|_| ()).is_err());
+        // failed attempt doesn't touch firmware's callback
+        assert!(status_changed_calls(&bt).is_empty());
+        drop(guard);
+        status_changed_calls(&bt);
+        let guard = bt.set_status_changed_callback(|_| ()).unwrap();
+        assert!(matches!(status_changed_calls(&bt)[..], [(Some(_), _)]));
+        drop(guard);
+    }
+
+    #[test]
+    fn key_names_ignore_case() {
+        assert_eq!(Key::from_name("Spacebar"), Some(Key::Spacebar));
+        assert_eq!(Key::from_name("leftarrow"), Some(Key::LeftArrow));
+        assert_eq!(Key::from_name("Nope"), None);
+        assert_eq!(Key::Spacebar.name(), Some("Spacebar"));
+        assert_eq!(Key::from_code(0x04), Key::A);
+    }
+
+    #[test]
+    fn key_modifiers_by_name() {
+        assert_eq!(
+            KeyMods::from_name_lenient("RightAlt"),
+            Some(KeyMods::RightAlt)
+        );
+        assert_eq!(
+            KeyMods::from_name_lenient("shift"),
+            Some(KeyMods::LeftShift)
+        );
+        assert_eq!(KeyMods::from_name_lenient("Hyper"), None);
+    }
+
+    #[test]
+    fn consumer_keys_by_name_or_hex_id() {
+        assert_eq!(
+            "volumeincrease".parse().ok(),
+            Some(ConsumerKey::VolumeIncrease)
+        );
+        assert_eq!("0x224".parse().ok(), Some(ConsumerKey::AcBack));
+        assert_eq!("0X224".parse().ok(), Some(ConsumerKey::AcBack));
+        assert_eq!("0x3FF".parse().ok(), Some(ConsumerKey::Other(0x3FF)));
+        assert!("0x".parse::<ConsumerKey>().is_err());
+        assert!("Nope".parse::<ConsumerKey>().is_err());
+    }
+
+    #[test]
+    fn unnamed_usages_display_as_hex() {
+        assert_eq!(ConsumerKey::AcBack.to_string(), "AcBack");
+        assert_eq!(ConsumerKey::Other(0x3FF).to_string(), "0x3FF");
+        assert_eq!(
+            ConsumerKey::Other(0x3FF).to_string().parse().ok(),
+            Some(ConsumerKey::Other(0x3FF))
+        );
+    }
+
+    #[test]
+    fn key_combo_round_trip() {
+        let combo = Key::Comma | KeyMods::LeftShift | KeyMods::RightCtrl;
+        assert_eq!(combo.to_string(), "LeftShift+RightCtrl+Comma");
+        assert_eq!(combo.to_string().parse().ok(), Some(combo));
+        assert_eq!(
+            "Shift+Comma".parse().ok(),
+            Some(Key::Comma | KeyMods::LeftShift)
+        );
+        assert_eq!(KeyCombo::from(u16::from(combo)), combo);
     }
 }
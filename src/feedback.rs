@@ -0,0 +1,44 @@
+//! Vibration and LED blinks acknowledging button presses
+
+use core::ffi::CStr;
+
+use flipperzero_sys::{self as sys, furi::UnsafeRecord};
+
+use crate::settings::Settings;
+
+pub const RECORD_NOTIFICATION: &CStr = c"notification";
+
+pub struct Feedback {
+    hnd: UnsafeRecord<sys::NotificationApp>,
+}
+
+impl Feedback {
+    pub fn open() -> Self {
+        let hnd = unsafe { UnsafeRecord::open(RECORD_NOTIFICATION) };
+        Self { hnd }
+    }
+
+    pub fn as_ptr(&self) -> *mut sys::NotificationApp {
+        self.hnd.as_ptr()
+    }
+
+    /// Acknowledges button press, in ways enabled in `settings`
+    pub fn press(&self, settings: &Settings) {
+        if settings.haptic {
+            unsafe {
+                sys::notification_message(
+                    self.as_ptr(),
+                    &raw const sys::sequence_single_vibro,
+                )
+            };
+        }
+        if settings.led {
+            unsafe {
+                sys::notification_message(
+                    self.as_ptr(),
+                    &raw const sys::sequence_blink_blue_10,
+                )
+            };
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use core::cell::RefCell;
+
+use alloc::vec::Vec;
+
+use crate::{
+    Error,
+    bt::{ConsumerKey, KeyCombo, MouseButton},
+};
+
+/// Anything that can receive HID reports.
+///
+/// Implemented by [`BleProfileBase`](crate::bt::BleProfileBase) for the real
+/// thing, and by [`RecordingHid`] for running remote logic on the host.
+pub trait HidSink {
+    fn key_press(&self, key: KeyCombo) -> Result<(), Error>;
+    fn key_release(&self, key: KeyCombo) -> Result<(), Error>;
+    fn key_release_all(&self) -> Result<(), Error>;
+
+    fn consumer_key_press(&self, button: ConsumerKey) -> Result<(), Error>;
+    fn consumer_key_release(&self, button: ConsumerKey) -> Result<(), Error>;
+    fn consumer_key_release_all(&self) -> Result<(), Error>;
+
+    fn mouse_press(&self, button: MouseButton) -> Result<(), Error>;
+    fn mouse_release(&self, button: MouseButton) -> Result<(), Error>;
+    fn mouse_release_all(&self) -> Result<(), Error>;
+    fn mouse_move(&self, dx: i8, dy: i8) -> Result<(), Error>;
+    fn mouse_scroll(&self, delta: i8) -> Result<(), Error>;
+}
+
+impl<T: HidSink + ?Sized> HidSink for &T {
+    fn key_press(&self, key: KeyCombo) -> Result<(), Error> {
+        (**self).key_press(key)
+    }
+
+    fn key_release(&self, key: KeyCombo) -> Result<(), Error> {
+        (**self).key_release(key)
+    }
+
+    fn key_release_all(&self) -> Result<(), Error> {
+        (**self).key_release_all()
+    }
+
+    fn consumer_key_press(&self, button: ConsumerKey) -> Result<(), Error> {
+        (**self).consumer_key_press(button)
+    }
+
+    fn consumer_key_release(&self, button: ConsumerKey) -> Result<(), Error> {
+        (**self).consumer_key_release(button)
+    }
+
+    fn consumer_key_release_all(&self) -> Result<(), Error> {
+        (**self).consumer_key_release_all()
+    }
+
+    fn mouse_press(&self, button: MouseButton) -> Result<(), Error> {
+        (**self).mouse_press(button)
+    }
+
+    fn mouse_release(&self, button: MouseButton) -> Result<(), Error> {
+        (**self).mouse_release(button)
+    }
+
+    fn mouse_release_all(&self) -> Result<(), Error> {
+        (**self).mouse_release_all()
+    }
+
+    fn mouse_move(&self, dx: i8, dy: i8) -> Result<(), Error> {
+        (**self).mouse_move(dx, dy)
+    }
+
+    fn mouse_scroll(&self, delta: i8) -> Result<(), Error> {
+        (**self).mouse_scroll(delta)
+    }
+}
+
+/// Single call made on a [`HidSink`], as seen by [`RecordingHid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HidEvent {
+    KeyPress(KeyCombo),
+    KeyRelease(KeyCombo),
+    KeyReleaseAll,
+    ConsumerKeyPress(ConsumerKey),
+    ConsumerKeyRelease(ConsumerKey),
+    ConsumerKeyReleaseAll,
+    MousePress(MouseButton),
+    MouseRelease(MouseButton),
+    MouseReleaseAll,
+    MouseMove(i8, i8),
+    MouseScroll(i8),
+}
+
+/// In-memory [`HidSink`] that records every call instead of sending it.
+#[derive(Default)]
+pub struct RecordingHid {
+    events: RefCell<Vec<HidEvent>>,
+}
+
+impl RecordingHid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<HidEvent> {
+        self.events.borrow().clone()
+    }
+
+    /// Returns recorded events, leaving the recording empty
+    pub fn take(&self) -> Vec<HidEvent> {
+        self.events.take()
+    }
+
+    pub fn clear(&self) {
+        self.events.borrow_mut().clear()
+    }
+
+    fn record(&self, event: HidEvent) -> Result<(), Error> {
+        self.events.borrow_mut().push(event);
+        Ok(())
+    }
+}
+
+impl HidSink for RecordingHid {
+    fn key_press(&self, key: KeyCombo) -> Result<(), Error> {
+        self.record(HidEvent::KeyPress(key))
+    }
+
+    fn key_release(&self, key: KeyCombo) -> Result<(), Error> {
+        self.record(HidEvent::KeyRelease(key))
+    }
+
+    fn key_release_all(&self) -> Result<(), Error> {
+        self.record(HidEvent::KeyReleaseAll)
+    }
+
+    fn consumer_key_press(&self, button: ConsumerKey) -> Result<(), Error> {
+        self.record(HidEvent::ConsumerKeyPress(button))
+    }
+
+    fn consumer_key_release(&self, button: ConsumerKey) -> Result<(), Error> {
+        self.record(HidEvent::ConsumerKeyRelease(button))
+    }
+
+    fn consumer_key_release_all(&self) -> Result<(), Error> {
+        self.record(HidEvent::ConsumerKeyReleaseAll)
+    }
+
+    fn mouse_press(&self, button: MouseButton) -> Result<(), Error> {
+        self.record(HidEvent::MousePress(button))
+    }
+
+    fn mouse_release(&self, button: MouseButton) -> Result<(), Error> {
+        self.record(HidEvent::MouseRelease(button))
+    }
+
+    fn mouse_release_all(&self) -> Result<(), Error> {
+        self.record(HidEvent::MouseReleaseAll)
+    }
+
+    fn mouse_move(&self, dx: i8, dy: i8) -> Result<(), Error> {
+        self.record(HidEvent::MouseMove(dx, dy))
+    }
+
+    fn mouse_scroll(&self, delta: i8) -> Result<(), Error> {
+        self.record(HidEvent::MouseScroll(delta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{
+        bt::{Key, KeyMods},
+        keymap::{Keymap, Mode},
+        view_port::{InputKey, InputType},
+    };
+
+    /// Maps input with builtin keymap and sends resulting actions
+    fn send(key: InputKey, type_: InputType, mode: Mode) -> Vec<HidEvent> {
+        let hid = RecordingHid::new();
+        for action in Keymap::builtin().map(key, type_, mode) {
+            action.send(&hid).unwrap();
+        }
+        hid.take()
+    }
+
+    #[test]
+    fn key_tap_presses_then_releases() {
+        let space = Key::Spacebar.into();
+        assert_eq!(
+            send(InputKey::Ok, InputType::Short, Mode::Basic),
+            [HidEvent::KeyPress(space), HidEvent::KeyRelease(space)],
+        );
+    }
+
+    #[test]
+    fn key_tap_keeps_modifiers() {
+        let combo = Key::Comma | KeyMods::LeftShift;
+        assert_eq!(
+            send(InputKey::Left, InputType::Long, Mode::Basic),
+            [HidEvent::KeyPress(combo), HidEvent::KeyRelease(combo)],
+        );
+    }
+
+    #[test]
+    fn consumer_tap_presses_then_releases() {
+        let volume = ConsumerKey::VolumeIncrease;
+        assert_eq!(
+            send(InputKey::Up, InputType::Long, Mode::Basic),
+            [
+                HidEvent::ConsumerKeyPress(volume),
+                HidEvent::ConsumerKeyRelease(volume)
+            ],
+        );
+    }
+
+    #[test]
+    fn mouse_click_presses_then_releases() {
+        assert_eq!(
+            send(InputKey::Ok, InputType::Short, Mode::Mouse),
+            [
+                HidEvent::MousePress(MouseButton::M1),
+                HidEvent::MouseRelease(MouseButton::M1)
+            ],
+        );
+    }
+
+    #[test]
+    fn stateful_actions_send_nothing() {
+        assert_eq!(send(InputKey::Back, InputType::Short, Mode::Basic), []);
+        assert_eq!(send(InputKey::Back, InputType::Long, Mode::Mouse), []);
+        assert_eq!(send(InputKey::Up, InputType::Press, Mode::Mouse), []);
+    }
+
+    #[test]
+    fn unbound_input_sends_nothing() {
+        assert_eq!(send(InputKey::Ok, InputType::Press, Mode::Basic), []);
+    }
+
+    #[test]
+    fn reference_forwards_to_sink() {
+        let hid = RecordingHid::new();
+        let by_ref = &&hid;
+        by_ref.mouse_move(3, -4).unwrap();
+        by_ref.mouse_scroll(-1).unwrap();
+        by_ref.key_release_all().unwrap();
+        assert_eq!(
+            hid.events(),
+            vec![
+                HidEvent::MouseMove(3, -4),
+                HidEvent::MouseScroll(-1),
+                HidEvent::KeyReleaseAll
+            ],
+        );
+        assert_eq!(hid.take().len(), 3);
+        assert_eq!(hid.events(), []);
+    }
+}
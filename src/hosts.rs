@@ -0,0 +1,48 @@
+//! Hosts remote remembers, each in its own slot with separate pairing and
+//! identity, so that switching between them doesn't need pairing again
+
+use alloc::{ffi::CString, format, string::String};
+
+/// Number of host slots
+pub const HOST_SLOTS: usize = 4;
+
+/// Longest host name, in bytes
+pub const MAX_HOST_NAME_LEN: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host {
+    /// Shown in host picker, see [`Host::is_valid_name`]
+    pub name: String,
+    /// Identity host sees, applied by restarting HID profile, see
+    /// [`HidProfileParams::with_mac_xor`](crate::bt::HidProfileParams::with_mac_xor)
+    pub mac_xor: u16,
+}
+
+impl Host {
+    /// Default host in slot `index`, every slot having different identity
+    pub fn new(index: usize) -> Self {
+        Self {
+            name: format!("Host {}", index + 1),
+            mac_xor: index as u16 + 1,
+        }
+    }
+
+    /// Not empty, up to [`MAX_HOST_NAME_LEN`] bytes, and can be written to
+    /// settings file as is: no `#`, control characters or surrounding
+    /// whitespace
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= MAX_HOST_NAME_LEN
+            && name.trim() == name
+            && !name.chars().any(|c| c == '#' || c.is_control())
+    }
+}
+
+/// Where bonding keys of host in slot `index` are kept. First slot uses the
+/// path from before there were slots, so that existing pairing is kept.
+pub fn key_storage_path(index: usize) -> CString {
+    match index {
+        0 => c"/data/.bt_hid.keys".into(),
+        _ => CString::new(format!("/data/.bt_hid_{}.keys", index + 1)).unwrap(),
+    }
+}
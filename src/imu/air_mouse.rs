@@ -0,0 +1,281 @@
+use super::{Axis, ImuSample, ImuSource};
+
+/// Tuning of [`AirMouse`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AirMouseConfig {
+    /// Cursor pixels per degree of rotation
+    pub sensitivity: f32,
+    /// Angular speed below which hand is considered still, in deg/s
+    pub deadzone: f32,
+    /// Weight of newest sample in moving average, `1.0` turns smoothing off
+    pub smoothing: f32,
+    /// How many samples are averaged into gyro bias on recentering
+    pub calibration_samples: u16,
+    /// Gyro axis turning which moves cursor right
+    pub x_axis: Axis,
+    /// Gyro axis turning which moves cursor down
+    pub y_axis: Axis,
+    /// Longer gaps between samples aren't integrated, so that pausing
+    /// doesn't make cursor jump
+    pub max_dt_ms: u32,
+}
+
+impl Default for AirMouseConfig {
+    /// Flipper held flat, screen up, top edge pointing at screen
+    fn default() -> Self {
+        Self {
+            sensitivity: 20.0,
+            deadzone: 3.0,
+            smoothing: 0.5,
+            calibration_samples: 32,
+            x_axis: Axis::NegZ,
+            y_axis: Axis::Y,
+            max_dt_ms: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Calibration {
+    sum: [f32; 3],
+    count: u16,
+}
+
+/// Turns angular velocity into cursor movement.
+///
+/// Starts with calibration, during which device should be held still, so
+/// that gyro bias can be measured. Recentering repeats it.
+pub struct AirMouse {
+    config: AirMouseConfig,
+    bias: [f32; 3],
+    calibration: Option<Calibration>,
+    /// Smoothed angular velocity along screen axes
+    velocity: (f32, f32),
+    /// Sub-pixel movement carried over to next sample
+    remainder: (f32, f32),
+    last_timestamp_ms: Option<u32>,
+}
+
+impl AirMouse {
+    pub fn new(config: AirMouseConfig) -> Self {
+        let mut air_mouse = Self {
+            config,
+            bias: [0.0; 3],
+            calibration: None,
+            velocity: (0.0, 0.0),
+            remainder: (0.0, 0.0),
+            last_timestamp_ms: None,
+        };
+        air_mouse.recenter();
+        air_mouse
+    }
+
+    pub fn config(&self) -> &AirMouseConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: AirMouseConfig) {
+        self.config = config;
+    }
+
+    /// Stops cursor and measures gyro bias again from next samples
+    pub fn recenter(&mut self) {
+        self.calibration = Some(Calibration::default());
+        self.velocity = (0.0, 0.0);
+        self.remainder = (0.0, 0.0);
+        self.last_timestamp_ms = None;
+    }
+
+    pub fn is_calibrating(&self) -> bool {
+        self.calibration.is_some()
+    }
+
+    /// Gyro bias measured during last calibration, in deg/s
+    pub fn bias(&self) -> [f32; 3] {
+        self.bias
+    }
+
+    /// Feeds single sample, returning cursor delta since previous one.
+    ///
+    /// Delta can be bigger than fits in single report, see
+    /// [`split_delta`](crate::mouse::split_delta).
+    pub fn process(&mut self, sample: &ImuSample) -> Option<(i32, i32)> {
+        let last_timestamp_ms =
+            self.last_timestamp_ms.replace(sample.timestamp_ms);
+
+        if let Some(calibration) = &mut self.calibration {
+            for (sum, rate) in calibration.sum.iter_mut().zip(sample.gyro) {
+                *sum += rate;
+            }
+            calibration.count += 1;
+            if calibration.count >= self.config.calibration_samples {
+                let count = calibration.count as f32;
+                self.bias = calibration.sum.map(|sum| sum / count);
+                self.calibration = None;
+            }
+            return None;
+        }
+
+        let dt_ms = sample.timestamp_ms.wrapping_sub(last_timestamp_ms?);
+        if dt_ms == 0 || dt_ms > self.config.max_dt_ms {
+            return None;
+        }
+
+        let mut rate = sample.gyro;
+        for (rate, bias) in rate.iter_mut().zip(self.bias) {
+            *rate -= bias;
+        }
+        let (x, y) =
+            (self.config.x_axis.pick(rate), self.config.y_axis.pick(rate));
+        let alpha = self.config.smoothing.clamp(0.0, 1.0);
+        self.velocity.0 += alpha * (x - self.velocity.0);
+        self.velocity.1 += alpha * (y - self.velocity.1);
+
+        let (vx, vy) = self.velocity;
+        let deadzone = self.config.deadzone;
+        if vx * vx + vy * vy < deadzone * deadzone {
+            return None;
+        }
+
+        let scale = self.config.sensitivity * dt_ms as f32 / 1000.0;
+        let (dx, dy) =
+            (vx * scale + self.remainder.0, vy * scale + self.remainder.1);
+        let (px, py) = (dx as i32, dy as i32);
+        self.remainder = (dx - px as f32, dy - py as f32);
+        (px != 0 || py != 0).then_some((px, py))
+    }
+
+    /// Processes all samples `source` has ready, returning summed cursor
+    /// delta
+    pub fn poll(&mut self, source: &mut impl ImuSource) -> Option<(i32, i32)> {
+        let mut total = None;
+        while let Some(sample) = source.read() {
+            if let Some((dx, dy)) = self.process(&sample) {
+                let (x, y) = total.get_or_insert((0, 0));
+                *x += dx;
+                *y += dy;
+            }
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::imu::Replay;
+
+    const CONFIG: AirMouseConfig = AirMouseConfig {
+        sensitivity: 20.0,
+        deadzone: 3.0,
+        smoothing: 1.0,
+        calibration_samples: 4,
+        x_axis: Axis::NegZ,
+        y_axis: Axis::Y,
+        max_dt_ms: 100,
+    };
+
+    /// `count` samples of constant angular velocity, 10 ms apart, starting
+    /// at `start_ms`
+    fn trace(start_ms: u32, count: u32, gyro: [f32; 3]) -> Vec<ImuSample> {
+        (0..count)
+            .map(|i| ImuSample {
+                gyro,
+                accel: [0.0, 0.0, 1.0],
+                timestamp_ms: start_ms + i * 10,
+            })
+            .collect()
+    }
+
+    /// Air mouse that measured `bias`, last sample taken at 30 ms
+    fn calibrated(config: AirMouseConfig, bias: [f32; 3]) -> AirMouse {
+        let mut air_mouse = AirMouse::new(config);
+        assert_eq!(air_mouse.poll(&mut Replay::new(trace(0, 4, bias))), None);
+        air_mouse
+    }
+
+    #[test]
+    fn calibration_measures_bias() {
+        let mut air_mouse = AirMouse::new(CONFIG);
+        assert!(air_mouse.is_calibrating());
+        let samples = [trace(0, 2, [1.0, 2.0, -3.0]), trace(20, 2, [3.0; 3])];
+        let mut replay = Replay::new(samples.concat());
+        assert_eq!(air_mouse.poll(&mut replay), None);
+        assert!(!air_mouse.is_calibrating());
+        assert_eq!(air_mouse.bias(), [2.0, 2.5, 0.0]);
+    }
+
+    #[test]
+    fn bias_is_subtracted() {
+        let bias = [10.0, -20.0, 30.0];
+        let mut air_mouse = calibrated(CONFIG, bias);
+        // holding still
+        let mut replay = Replay::new(trace(40, 10, bias));
+        assert_eq!(air_mouse.poll(&mut replay), None);
+        // turning right at 100 deg/s, 20 px per 10 ms sample
+        let mut replay = Replay::new(trace(140, 5, [10.0, -20.0, -70.0]));
+        assert_eq!(air_mouse.poll(&mut replay), Some((100, 0)));
+    }
+
+    #[test]
+    fn axes_map_onto_screen() {
+        let mut air_mouse = calibrated(CONFIG, [0.0; 3]);
+        let mut replay = Replay::new(trace(40, 1, [0.0, 50.0, 50.0]));
+        assert_eq!(air_mouse.poll(&mut replay), Some((-10, 10)));
+    }
+
+    #[test]
+    fn deadzone_ignores_slow_turns() {
+        let mut air_mouse = calibrated(CONFIG, [0.0; 3]);
+        let mut replay = Replay::new(trace(40, 10, [2.0, 2.0, 0.0]));
+        assert_eq!(air_mouse.poll(&mut replay), None);
+        // 1 px per sample, just past deadzone
+        let mut replay = Replay::new(trace(140, 10, [0.0, 0.0, -5.0]));
+        assert_eq!(air_mouse.poll(&mut replay), Some((10, 0)));
+    }
+
+    #[test]
+    fn smoothing_eases_into_motion() {
+        let config = AirMouseConfig {
+            smoothing: 0.5,
+            ..CONFIG
+        };
+        let mut air_mouse = calibrated(config, [0.0; 3]);
+        // velocity goes 50, 75, 87.5 deg/s on the way to 100
+        let deltas: Vec<_> = trace(40, 3, [0.0, 0.0, -100.0])
+            .into_iter()
+            .map(|sample| air_mouse.poll(&mut Replay::new([sample])))
+            .collect();
+        assert_eq!(deltas, [Some((10, 0)), Some((15, 0)), Some((17, 0))]);
+    }
+
+    #[test]
+    fn gaps_between_samples_are_skipped() {
+        let mut air_mouse = calibrated(CONFIG, [0.0; 3]);
+        let turning = [0.0, 0.0, -100.0];
+        // sample 500 ms after last one isn't integrated
+        let mut replay = Replay::new(trace(530, 1, turning));
+        assert_eq!(air_mouse.poll(&mut replay), None);
+        // but next ones are
+        let mut replay = Replay::new(trace(540, 1, turning));
+        assert_eq!(air_mouse.poll(&mut replay), Some((20, 0)));
+        // as are ones exactly `max_dt_ms` apart, but not repeated ones
+        let samples = [trace(640, 1, turning), trace(640, 1, turning)];
+        let mut replay = Replay::new(samples.concat());
+        assert_eq!(air_mouse.poll(&mut replay), Some((200, 0)));
+    }
+
+    #[test]
+    fn recenter_calibrates_again() {
+        let mut air_mouse = calibrated(CONFIG, [0.0; 3]);
+        air_mouse.recenter();
+        assert!(air_mouse.is_calibrating());
+        let drifting = [0.0, 0.0, -50.0];
+        let mut replay = Replay::new(trace(40, 4, drifting));
+        assert_eq!(air_mouse.poll(&mut replay), None);
+        let mut replay = Replay::new(trace(80, 10, drifting));
+        assert_eq!(air_mouse.poll(&mut replay), None);
+    }
+}
@@ -0,0 +1,410 @@
+use alloc::vec::Vec;
+
+use super::{Axis, ImuSample, ImuSource};
+use crate::{
+    keymap::{Action, Keymap, Mode},
+    view_port::{InputKey, InputType},
+};
+
+/// Wrist movement standing in for button press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    /// Quick turn to the left and back
+    FlickLeft,
+    FlickRight,
+    /// Top edge held raised, repeats while held
+    TiltUp,
+    TiltDown,
+    Shake,
+}
+
+impl Gesture {
+    /// Basic mode input this gesture acts as
+    pub fn input(self) -> (InputKey, InputType) {
+        match self {
+            Gesture::FlickLeft => (InputKey::Left, InputType::Short),
+            Gesture::FlickRight => (InputKey::Right, InputType::Short),
+            Gesture::TiltUp => (InputKey::Up, InputType::Long),
+            Gesture::TiltDown => (InputKey::Down, InputType::Long),
+            Gesture::Shake => (InputKey::Ok, InputType::Short),
+        }
+    }
+
+    /// Actions `keymap` binds to this gesture's input in Basic mode, so
+    /// seek, volume and play/pause by default
+    pub fn actions(self, keymap: &Keymap) -> Vec<Action> {
+        let (key, type_) = self.input();
+        keymap.map(key, type_, Mode::Basic)
+    }
+}
+
+/// Thresholds of [`GestureRecognizer`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureConfig {
+    /// Gyro axis of flicks, turning towards positive is right
+    pub flick_axis: Axis,
+    /// Angular speed flick has to reach, in deg/s
+    pub flick_rate: f32,
+    /// Accelerometer axis pointing up when top edge is raised
+    pub tilt_axis: Axis,
+    /// Gravity along [`Self::tilt_axis`] counting as tilt, in g. `0.5` is
+    /// about 30 degrees
+    pub tilt_threshold: f32,
+    /// How long tilt has to be held before first step, in ms
+    pub tilt_hold_ms: u32,
+    /// Time between steps while tilt is held, in ms
+    pub tilt_repeat_ms: u32,
+    /// Acceleration above which movement counts as a jolt, in g
+    pub shake_threshold: f32,
+    /// Jolts making a shake
+    pub shake_count: usize,
+    /// Time all jolts of a shake have to fit in, in ms
+    pub shake_window_ms: u32,
+    /// Time after flick or shake during which new ones are ignored, so
+    /// that hand moving back doesn't trigger another, in ms
+    pub cooldown_ms: u32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            flick_axis: Axis::NegZ,
+            flick_rate: 200.0,
+            tilt_axis: Axis::X,
+            tilt_threshold: 0.5,
+            tilt_hold_ms: 200,
+            tilt_repeat_ms: 300,
+            shake_threshold: 2.0,
+            shake_count: 4,
+            shake_window_ms: 800,
+            cooldown_ms: 400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flick {
+    /// Turning fast, towards right if `true`
+    Turning(bool),
+    /// Turning slowed down, waiting to see if it wasn't start of shake
+    Done(bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Tilt {
+    up: bool,
+    /// When next step is due
+    next_ms: u32,
+}
+
+/// Recognizes gestures in stream of samples.
+///
+/// Flick is reported once turning slows down again and no jolts were felt
+/// recently, so that shake, which can start like one, cancels it.
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    flick: Option<Flick>,
+    tilt: Option<Tilt>,
+    in_jolt: bool,
+    /// Shake was reported and jolts didn't stop yet
+    shaking: bool,
+    /// Timestamps of recent jolts
+    jolts: Vec<u32>,
+    last_gesture_ms: Option<u32>,
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            flick: None,
+            tilt: None,
+            in_jolt: false,
+            shaking: false,
+            jolts: Vec::new(),
+            last_gesture_ms: None,
+        }
+    }
+
+    pub fn config(&self) -> &GestureConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: GestureConfig) {
+        self.config = config;
+    }
+
+    /// Forgets any half-recognized gesture
+    pub fn reset(&mut self) {
+        self.flick = None;
+        self.tilt = None;
+        self.in_jolt = false;
+        self.shaking = false;
+        self.jolts.clear();
+        self.last_gesture_ms = None;
+    }
+
+    fn cooling_down(&self, now: u32) -> bool {
+        self.last_gesture_ms.is_some_and(|last| {
+            now.wrapping_sub(last) < self.config.cooldown_ms
+        })
+    }
+
+    fn emit(&mut self, gesture: Gesture, now: u32) -> Option<Gesture> {
+        self.last_gesture_ms = Some(now);
+        Some(gesture)
+    }
+
+    /// Feeds single sample, returning gesture it completed, if any
+    pub fn process(&mut self, sample: &ImuSample) -> Option<Gesture> {
+        let now = sample.timestamp_ms;
+        let config = self.config;
+
+        let [x, y, z] = sample.accel;
+        let jolt = x * x + y * y + z * z
+            > config.shake_threshold * config.shake_threshold;
+        if jolt && !self.in_jolt {
+            self.jolts.push(now);
+        }
+        self.in_jolt = jolt;
+        self.jolts
+            .retain(|&t| now.wrapping_sub(t) <= config.shake_window_ms);
+        if self.jolts.is_empty() {
+            self.shaking = false;
+        }
+        // single shake for any length of shaking
+        if !self.shaking && self.jolts.len() >= config.shake_count {
+            self.shaking = true;
+            self.flick = None;
+            self.tilt = None;
+            return self.emit(Gesture::Shake, now);
+        }
+
+        let rate = config.flick_axis.pick(sample.gyro);
+        let flick_rate = config.flick_rate;
+        match self.flick {
+            None if rate * rate >= flick_rate * flick_rate => {
+                if !self.cooling_down(now) {
+                    self.flick = Some(Flick::Turning(rate > 0.0));
+                }
+            }
+            // done once turning slows down to half speed
+            Some(Flick::Turning(right))
+                if rate * rate * 4.0 < flick_rate * flick_rate =>
+            {
+                self.flick = Some(Flick::Done(right));
+            }
+            _ => (),
+        }
+        if !self.jolts.is_empty() {
+            // wait for shake to either happen or die down
+            if self.shaking {
+                self.flick = None;
+            }
+            self.tilt = None;
+            return None;
+        }
+        if let Some(flick) = self.flick {
+            self.tilt = None;
+            let Flick::Done(right) = flick else {
+                return None;
+            };
+            self.flick = None;
+            let gesture = if right {
+                Gesture::FlickRight
+            } else {
+                Gesture::FlickLeft
+            };
+            return self.emit(gesture, now);
+        }
+
+        let gravity = config.tilt_axis.pick(sample.accel);
+        let up = gravity > 0.0;
+        if gravity * gravity < config.tilt_threshold * config.tilt_threshold {
+            self.tilt = None;
+            return None;
+        }
+        match self.tilt {
+            Some(tilt) if tilt.up == up => {
+                // wrapping "now >= next_ms"
+                if (now.wrapping_sub(tilt.next_ms) as i32) < 0 {
+                    return None;
+                }
+                self.tilt = Some(Tilt {
+                    up,
+                    next_ms: now.wrapping_add(config.tilt_repeat_ms),
+                });
+                let gesture = if up {
+                    Gesture::TiltUp
+                } else {
+                    Gesture::TiltDown
+                };
+                Some(gesture)
+            }
+            _ => {
+                self.tilt = Some(Tilt {
+                    up,
+                    next_ms: now.wrapping_add(config.tilt_hold_ms),
+                });
+                None
+            }
+        }
+    }
+
+    /// Processes all samples `source` has ready, returning recognized
+    /// gestures
+    pub fn poll(&mut self, source: &mut impl ImuSource) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+        while let Some(sample) = source.read() {
+            gestures.extend(self.process(&sample));
+        }
+        gestures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bt::Key,
+        imu::{ImuSample, Replay},
+    };
+
+    /// Flipper lying flat
+    const FLAT: [f32; 3] = [0.0, 0.0, 1.0];
+    const STILL: [f32; 3] = [0.0; 3];
+    /// Turning right fast enough to flick
+    const RIGHT: [f32; 3] = [0.0, 0.0, -300.0];
+    const LEFT: [f32; 3] = [0.0, 0.0, 300.0];
+
+    /// Samples 10 ms apart, from `start_ms` up to `end_ms`
+    fn hold(
+        start_ms: u32,
+        end_ms: u32,
+        gyro: [f32; 3],
+        accel: [f32; 3],
+    ) -> Vec<ImuSample> {
+        (start_ms..end_ms)
+            .step_by(10)
+            .map(|timestamp_ms| ImuSample {
+                gyro,
+                accel,
+                timestamp_ms,
+            })
+            .collect()
+    }
+
+    fn recognize(trace: &[Vec<ImuSample>]) -> Vec<Gesture> {
+        let mut recognizer = GestureRecognizer::new(GestureConfig::default());
+        recognizer.poll(&mut Replay::new(trace.concat()))
+    }
+
+    #[test]
+    fn flick_right() {
+        let trace = [
+            hold(0, 50, STILL, FLAT),
+            hold(50, 100, RIGHT, FLAT),
+            hold(100, 300, STILL, FLAT),
+        ];
+        assert_eq!(recognize(&trace), [Gesture::FlickRight]);
+    }
+
+    #[test]
+    fn flick_left() {
+        let trace = [hold(0, 50, LEFT, FLAT), hold(50, 300, STILL, FLAT)];
+        assert_eq!(recognize(&trace), [Gesture::FlickLeft]);
+    }
+
+    #[test]
+    fn slow_turn_isnt_flick() {
+        let trace = [
+            hold(0, 200, [0.0, 0.0, -150.0], FLAT),
+            hold(200, 300, STILL, FLAT),
+        ];
+        assert_eq!(recognize(&trace), []);
+    }
+
+    #[test]
+    fn flick_is_reported_once_turning_slows_down() {
+        let mut recognizer = GestureRecognizer::new(GestureConfig::default());
+        let mut turning = Replay::new(hold(0, 500, RIGHT, FLAT));
+        assert_eq!(recognizer.poll(&mut turning), []);
+        let mut stopped = Replay::new(hold(500, 510, STILL, FLAT));
+        assert_eq!(recognizer.poll(&mut stopped), [Gesture::FlickRight]);
+    }
+
+    #[test]
+    fn tilt_holds_then_repeats() {
+        let up = [0.7, 0.0, 0.7];
+        // steps at 200, 500 and 800 ms
+        let trace = [hold(0, 850, STILL, up), hold(850, 1000, STILL, FLAT)];
+        assert_eq!(recognize(&trace), [Gesture::TiltUp; 3]);
+        let down = [-0.7, 0.0, 0.7];
+        assert_eq!(
+            recognize(&[hold(0, 250, STILL, down)]),
+            [Gesture::TiltDown]
+        );
+    }
+
+    #[test]
+    fn short_tilt_does_nothing() {
+        let up = [0.7, 0.0, 0.7];
+        let trace = [hold(0, 150, STILL, up), hold(150, 400, STILL, FLAT)];
+        assert_eq!(recognize(&trace), []);
+    }
+
+    #[test]
+    fn shake_cancels_flick() {
+        // shaking starts like flick, with jolts every 40 ms
+        let jolt = [3.0, 0.0, 1.0];
+        let mut trace = Vec::new();
+        for start in (0..200).step_by(40) {
+            trace.push(hold(start, start + 10, RIGHT, jolt));
+            trace.push(hold(start + 10, start + 40, RIGHT, FLAT));
+        }
+        trace.push(hold(200, 1500, STILL, FLAT));
+        assert_eq!(recognize(&trace), [Gesture::Shake]);
+    }
+
+    #[test]
+    fn single_jolt_only_delays_flick() {
+        let jolt = [3.0, 0.0, 1.0];
+        let trace = [
+            hold(0, 10, RIGHT, jolt),
+            hold(10, 50, RIGHT, FLAT),
+            hold(50, 1500, STILL, FLAT),
+        ];
+        assert_eq!(recognize(&trace), [Gesture::FlickRight]);
+    }
+
+    #[test]
+    fn cooldown_ignores_hand_moving_back() {
+        let trace = [
+            hold(0, 50, RIGHT, FLAT),
+            // flick reported at 50 ms, hand comes back right after
+            hold(50, 100, STILL, FLAT),
+            hold(100, 150, LEFT, FLAT),
+            hold(150, 500, STILL, FLAT),
+            // cooldown is over
+            hold(500, 550, LEFT, FLAT),
+            hold(550, 600, STILL, FLAT),
+        ];
+        assert_eq!(
+            recognize(&trace),
+            [Gesture::FlickRight, Gesture::FlickLeft]
+        );
+    }
+
+    #[test]
+    fn gestures_act_as_basic_inputs() {
+        let keymap = Keymap::builtin();
+        assert_eq!(
+            Gesture::FlickLeft.actions(&keymap),
+            [Action::KeyTap(Key::LeftArrow.into())]
+        );
+        assert_eq!(
+            Gesture::Shake.actions(&keymap),
+            [Action::KeyTap(Key::Spacebar.into())]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Driver for ICM-42688-P, as found on Video Game Module, connected to
+//! external SPI bus on GPIO header
+
+use super::{ImuSample, ImuSource, sys};
+use crate::Error;
+
+const WHO_AM_I: u8 = 0x75;
+const WHO_AM_I_VALUE: u8 = 0x47;
+const DEVICE_CONFIG: u8 = 0x11;
+const SOFT_RESET: u8 = 0x01;
+const INT_STATUS: u8 = 0x2D;
+const DATA_RDY_INT: u8 = 1 << 3;
+/// First of 12 data registers: accel x, y, z then gyro x, y, z, big endian
+const ACCEL_DATA_X1: u8 = 0x1F;
+const PWR_MGMT0: u8 = 0x4E;
+/// Gyro and accel in low noise mode
+const PWR_MGMT0_LN: u8 = 0x0F;
+const GYRO_CONFIG0: u8 = 0x4F;
+const ACCEL_CONFIG0: u8 = 0x50;
+/// ±500 dps, 100 Hz
+const GYRO_CONFIG0_VALUE: u8 = (0b010 << 5) | 0x08;
+/// ±4 g, 100 Hz
+const ACCEL_CONFIG0_VALUE: u8 = (0b010 << 5) | 0x08;
+/// LSB per dps at ±500 dps
+const GYRO_SCALE: f32 = 65.5;
+/// LSB per g at ±4 g
+const ACCEL_SCALE: f32 = 8192.0;
+
+const READ: u8 = 0x80;
+const TIMEOUT_MS: u32 = 50;
+
+pub struct Icm42688 {
+    bus: Bus,
+}
+
+impl Icm42688 {
+    /// Probes and configures sensor, failing if there isn't one attached
+    pub fn open() -> Result<Self, Error> {
+        // deinits bus on early return, without touching whatever device
+        // answered instead of sensor
+        let bus = Bus::init();
+        if bus.read_reg(WHO_AM_I)? != WHO_AM_I_VALUE {
+            return Err(Error);
+        }
+        // puts sensor back to sleep if configuring it fails
+        let imu = Self { bus };
+
+        imu.bus.write_reg(DEVICE_CONFIG, SOFT_RESET)?;
+        unsafe { sys::furi_delay_ms(2) };
+        imu.bus.write_reg(GYRO_CONFIG0, GYRO_CONFIG0_VALUE)?;
+        imu.bus.write_reg(ACCEL_CONFIG0, ACCEL_CONFIG0_VALUE)?;
+        imu.bus.write_reg(PWR_MGMT0, PWR_MGMT0_LN)?;
+        // gyro needs 45ms to start up
+        unsafe { sys::furi_delay_ms(50) };
+        Ok(imu)
+    }
+
+    /// Reads current sample, or `None` if sensor didn't produce new one
+    /// since last read
+    pub fn read_sample(&self) -> Result<Option<ImuSample>, Error> {
+        if self.bus.read_reg(INT_STATUS)? & DATA_RDY_INT == 0 {
+            return Ok(None);
+        }
+        let mut data = [0; 12];
+        self.bus.read_regs(ACCEL_DATA_X1, &mut data)?;
+        let value = |i: usize| i16::from_be_bytes([data[i], data[i + 1]]);
+        let accel = [0, 2, 4].map(|i| value(i) as f32 / ACCEL_SCALE);
+        let gyro = [6, 8, 10].map(|i| value(i) as f32 / GYRO_SCALE);
+        let timestamp_ms = unsafe { sys::furi_get_tick() };
+        Ok(Some(ImuSample {
+            gyro,
+            accel,
+            timestamp_ms,
+        }))
+    }
+}
+
+impl ImuSource for Icm42688 {
+    fn read(&mut self) -> Option<ImuSample> {
+        self.read_sample().ok().flatten()
+    }
+}
+
+impl Drop for Icm42688 {
+    fn drop(&mut self) {
+        // put sensor to sleep, nothing to do if that fails
+        let _ = self.bus.write_reg(PWR_MGMT0, 0);
+    }
+}
+
+/// External SPI bus, initialized for as long as it's alive
+struct Bus {
+    handle: *const sys::FuriHalSpiBusHandle,
+}
+
+// handle points to firmware's static bus handle, and bus is acquired for
+// every transfer
+unsafe impl Send for Bus {}
+
+impl Bus {
+    fn init() -> Self {
+        let handle = &raw const sys::furi_hal_spi_bus_handle_external;
+        unsafe { sys::furi_hal_spi_bus_handle_init(handle) };
+        Self { handle }
+    }
+
+    fn transfer(&self, tx: &[u8], rx: &mut [u8]) -> Result<(), Error> {
+        assert_eq!(tx.len(), rx.len());
+        let res = unsafe {
+            sys::furi_hal_spi_acquire(self.handle);
+            let res = sys::furi_hal_spi_bus_trx(
+                self.handle,
+                tx.as_ptr(),
+                rx.as_mut_ptr(),
+                tx.len(),
+                TIMEOUT_MS,
+            );
+            sys::furi_hal_spi_release(self.handle);
+            res
+        };
+        res.then_some(()).ok_or(Error)
+    }
+
+    /// Burst reads registers starting at `reg` into `out`, up to 12 at once
+    fn read_regs(&self, reg: u8, out: &mut [u8]) -> Result<(), Error> {
+        let len = out.len() + 1;
+        let mut tx = [0; 13];
+        let mut rx = [0; 13];
+        tx[0] = reg | READ;
+        self.transfer(&tx[..len], &mut rx[..len])?;
+        out.copy_from_slice(&rx[1..len]);
+        Ok(())
+    }
+
+    fn read_reg(&self, reg: u8) -> Result<u8, Error> {
+        let mut out = [0];
+        self.read_regs(reg, &mut out)?;
+        Ok(out[0])
+    }
+
+    fn write_reg(&self, reg: u8, value: u8) -> Result<(), Error> {
+        self.transfer(&[reg, value], &mut [0; 2])
+    }
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        unsafe { sys::furi_hal_spi_bus_handle_deinit(self.handle) };
+    }
+}
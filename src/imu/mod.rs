@@ -1 +1,75 @@
+// pretends to be `use flipperzero_sys as sys;`
+#[cfg(target_os = "none")]
+mod sys;
 
+pub mod air_mouse;
+pub mod gesture;
+#[cfg(target_os = "none")]
+pub mod icm42688;
+
+/// Single IMU reading
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ImuSample {
+    /// Angular velocity around x, y and z axes, in degrees per second
+    pub gyro: [f32; 3],
+    /// Acceleration along x, y and z axes, in g
+    pub accel: [f32; 3],
+    /// When sample was taken, in milliseconds, wraps around
+    pub timestamp_ms: u32,
+}
+
+/// Anything producing IMU samples, either real sensor or a recording
+pub trait ImuSource {
+    /// Returns next sample, or `None` if there isn't new one yet
+    fn read(&mut self) -> Option<ImuSample>;
+}
+
+impl<T: ImuSource + ?Sized> ImuSource for &mut T {
+    fn read(&mut self) -> Option<ImuSample> {
+        (**self).read()
+    }
+}
+
+/// Plays back previously recorded samples
+pub struct Replay<I> {
+    samples: I,
+}
+
+impl<I: Iterator<Item = ImuSample>> Replay<I> {
+    pub fn new(samples: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            samples: samples.into_iter(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = ImuSample>> ImuSource for Replay<I> {
+    fn read(&mut self) -> Option<ImuSample> {
+        self.samples.next()
+    }
+}
+
+/// Sensor axis, optionally flipped, used to map sensor frame onto screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+    NegX,
+    NegY,
+    NegZ,
+}
+
+impl Axis {
+    /// Picks this axis' component out of `v`
+    pub fn pick(self, v: [f32; 3]) -> f32 {
+        match self {
+            Axis::X => v[0],
+            Axis::Y => v[1],
+            Axis::Z => v[2],
+            Axis::NegX => -v[0],
+            Axis::NegY => -v[1],
+            Axis::NegZ => -v[2],
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// lets me pretend this module is part of flipperzero_sys crate
+use flipperzero_sys as sys;
+pub use sys::*;
+
+unsafe extern "C" {
+    /** External SPI bus on GPIO header, CS on pin 4 (PA4) */
+    pub unsafe static furi_hal_spi_bus_handle_external:
+        sys::FuriHalSpiBusHandle;
+
+    /** Initialize SPI bus handle, set pins to SPI mode
+     *
+     * @param      handle  pointer to FuriHalSpiBusHandle instance
+     */
+    pub unsafe fn furi_hal_spi_bus_handle_init(
+        handle: *const sys::FuriHalSpiBusHandle,
+    );
+
+    /** Deinitialize SPI bus handle, set pins back to analog mode
+     *
+     * @param      handle  pointer to FuriHalSpiBusHandle instance
+     */
+    pub unsafe fn furi_hal_spi_bus_handle_deinit(
+        handle: *const sys::FuriHalSpiBusHandle,
+    );
+
+    /** Acquire SPI bus, locks bus and asserts CS
+     *
+     * @param      handle  pointer to FuriHalSpiBusHandle instance
+     */
+    pub unsafe fn furi_hal_spi_acquire(handle: *const sys::FuriHalSpiBusHandle);
+
+    /** Release SPI bus, deasserts CS and unlocks bus
+     *
+     * @param      handle  pointer to FuriHalSpiBusHandle instance
+     */
+    pub unsafe fn furi_hal_spi_release(handle: *const sys::FuriHalSpiBusHandle);
+
+    /** SPI Transmit and Receive
+     *
+     * @param      handle      pointer to FuriHalSpiBusHandle instance
+     * @param      tx_buffer   pointer to tx buffer
+     * @param      rx_buffer   pointer to rx buffer
+     * @param      size        transaction size (buffer sizes must be equal)
+     * @param      timeout     operation timeout in ms
+     *
+     * @return     true on success
+     */
+    pub unsafe fn furi_hal_spi_bus_trx(
+        handle: *const sys::FuriHalSpiBusHandle,
+        tx_buffer: *const u8,
+        rx_buffer: *mut u8,
+        size: usize,
+        timeout: u32,
+    ) -> bool;
+}
@@ -0,0 +1,895 @@
+use core::ffi::CStr;
+
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    Error,
+    bt::{ConsumerKey, Key, KeyCombo, KeyMods, MouseButton},
+    hid::HidSink,
+    mouse::Direction,
+    pattern::Pattern,
+    view_port::{InputKey, InputType},
+};
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Basic = 0,
+    Mouse = 1,
+    Scroll = 2,
+    AirMouse = 3,
+}
+
+/// What a single input event should do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Press and release a keyboard key
+    KeyTap(KeyCombo),
+    /// Press and release a consumer control key
+    ConsumerTap(ConsumerKey),
+    /// Press and release a mouse button
+    MouseClick(MouseButton),
+    MousePress(MouseButton),
+    MouseRelease(MouseButton),
+    /// Hold left mouse button until toggled again, or until next left click
+    ToggleDragLock,
+    MouseMove(i8, i8),
+    /// Turn wheel by given number of notches, positive scrolling up
+    MouseScroll(i8),
+    /// Start moving cursor while direction is held
+    StartMove(Direction),
+    StopMove(Direction),
+    /// Start scrolling while Up or Down is held
+    StartScroll(Direction),
+    StopScroll(Direction),
+    /// Recalibrate air mouse, device should be held still afterwards
+    Recenter,
+    SwitchMode(Mode),
+    OpenMenu,
+}
+
+impl Action {
+    /// Sends HID reports for this action.
+    ///
+    /// Drag lock, motion, recentering, [`Action::SwitchMode`] and
+    /// [`Action::OpenMenu`] actions depend on state, and are left for the
+    /// caller to handle.
+    pub fn send(&self, hid: &impl HidSink) -> Result<(), Error> {
+        match *self {
+            Action::KeyTap(key) => {
+                hid.key_press(key)?;
+                hid.key_release(key)
+            }
+            Action::ConsumerTap(key) => {
+                hid.consumer_key_press(key)?;
+                hid.consumer_key_release(key)
+            }
+            Action::MouseClick(button) => {
+                hid.mouse_press(button)?;
+                hid.mouse_release(button)
+            }
+            Action::MousePress(button) => hid.mouse_press(button),
+            Action::MouseRelease(button) => hid.mouse_release(button),
+            Action::MouseMove(dx, dy) => hid.mouse_move(dx, dy),
+            Action::MouseScroll(delta) => hid.mouse_scroll(delta),
+            Action::ToggleDragLock
+            | Action::StartMove(_)
+            | Action::StopMove(_)
+            | Action::StartScroll(_)
+            | Action::StopScroll(_)
+            | Action::Recenter
+            | Action::SwitchMode(_)
+            | Action::OpenMenu => Ok(()),
+        }
+    }
+}
+
+/// Path of user keymap, overriding [`Keymap::builtin`] bindings
+pub const KEYMAP_PATH: &CStr = c"/ext/apps_data/yt_remote/keymap.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub key: InputKey,
+    pub type_: InputType,
+    pub action: Action,
+}
+
+/// Basic mode bindings.
+///
+/// Back button is reserved for switching modes and exiting, and can't be
+/// rebound.
+///
+/// `Repeat` bindings are steps sent while input is held, see
+/// [`HoldRepeat`](crate::repeat::HoldRepeat). Patterns are recognized by
+/// [`PatternRecognizer`](crate::pattern::PatternRecognizer).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    /// Steps replacing `Repeat` bindings once repeating speeds up
+    fast_steps: Vec<(InputKey, Action)>,
+    patterns: Vec<(Pattern, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Keymap {
+    /// Bindings for YouTube
+    pub fn builtin() -> Self {
+        let shift = KeyMods::LeftShift;
+        let mut keymap = Self::from_table(&[
+            (
+                InputKey::Ok,
+                InputType::Short,
+                Action::KeyTap(Key::Spacebar.into()),
+            ),
+            (InputKey::Ok, InputType::Long, Action::KeyTap(Key::F.into())),
+            (
+                InputKey::Left,
+                InputType::Short,
+                Action::KeyTap(Key::LeftArrow.into()),
+            ),
+            (
+                InputKey::Right,
+                InputType::Short,
+                Action::KeyTap(Key::RightArrow.into()),
+            ),
+            (
+                InputKey::Left,
+                InputType::Long,
+                Action::KeyTap(Key::Comma | shift),
+            ),
+            (
+                InputKey::Right,
+                InputType::Long,
+                Action::KeyTap(Key::Dot | shift),
+            ),
+            (
+                InputKey::Up,
+                InputType::Short,
+                Action::KeyTap(Key::Dot.into()),
+            ),
+            (
+                InputKey::Down,
+                InputType::Short,
+                Action::KeyTap(Key::Comma.into()),
+            ),
+            (
+                InputKey::Up,
+                InputType::Long,
+                Action::ConsumerTap(ConsumerKey::VolumeIncrease),
+            ),
+            (
+                InputKey::Down,
+                InputType::Long,
+                Action::ConsumerTap(ConsumerKey::VolumeDecrease),
+            ),
+            (
+                InputKey::Left,
+                InputType::Repeat,
+                Action::KeyTap(Key::LeftArrow.into()),
+            ),
+            (
+                InputKey::Right,
+                InputType::Repeat,
+                Action::KeyTap(Key::RightArrow.into()),
+            ),
+            (
+                InputKey::Up,
+                InputType::Repeat,
+                Action::ConsumerTap(ConsumerKey::VolumeIncrease),
+            ),
+            (
+                InputKey::Down,
+                InputType::Repeat,
+                Action::ConsumerTap(ConsumerKey::VolumeDecrease),
+            ),
+        ]);
+        // 5s seeking turns into 10s
+        keymap
+            .set_fast_step(InputKey::Left, Some(Action::KeyTap(Key::J.into())));
+        keymap.set_fast_step(
+            InputKey::Right,
+            Some(Action::KeyTap(Key::L.into())),
+        );
+        keymap
+    }
+
+    pub fn from_table(table: &[(InputKey, InputType, Action)]) -> Self {
+        Self {
+            bindings: table
+                .iter()
+                .map(|&(key, type_, action)| Binding { key, type_, action })
+                .collect(),
+            fast_steps: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Parses keymap file on top of [`Keymap::builtin`].
+    ///
+    /// Each line binds single input to a key, optionally with modifiers, or
+    /// to a consumer key:
+    /// ```text
+    /// # comment
+    /// Ok Long = F
+    /// Left Long = Shift+Comma
+    /// Up Long = VolumeIncrease
+    /// Down Long = 0x224
+    /// Left Repeat = LeftArrow, J
+    /// Ok Double = F
+    /// Up+Down = M
+    /// ```
+    /// Names are case-insensitive, keyboard keys take precedence over
+    /// consumer keys with the same name, which can be given by usage ID
+    /// instead. Inputs not mentioned keep their builtin binding.
+    ///
+    /// `Repeat` binding can be followed by fast step, see
+    /// [`Keymap::fast_step`]. Besides firmware's input types, inputs can be
+    /// bound to `Double` or `Triple` tap and `VeryLong` hold, and pairs of
+    /// inputs joined with `+` to chords, see [`Pattern`].
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut keymap = Self::builtin();
+        for (i, line) in text.lines().enumerate() {
+            let error = |kind| ParseError { line: i + 1, kind };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (input, action) = line
+                .split_once('=')
+                .ok_or(error(ParseErrorKind::MissingEquals))?;
+            if let Some(pattern) = parse_pattern(input.trim()).map_err(error)? {
+                let action = parse_action(action.trim()).map_err(error)?;
+                keymap.bind_pattern(pattern, action);
+                continue;
+            }
+            let (action, fast_step) = match action.split_once(',') {
+                Some((action, fast_step)) => (action, Some(fast_step)),
+                None => (action, None),
+            };
+            let binding =
+                parse_binding(input.trim(), action.trim()).map_err(error)?;
+            if binding.type_ == InputType::Repeat {
+                let fast_step = fast_step
+                    .map(|fast_step| parse_action(fast_step.trim()))
+                    .transpose()
+                    .map_err(error)?;
+                keymap.set_fast_step(binding.key, fast_step);
+            } else if fast_step.is_some() {
+                return Err(error(ParseErrorKind::UnexpectedFastStep));
+            }
+            keymap.bind(binding);
+        }
+        Ok(keymap)
+    }
+
+    /// Adds binding, replacing previous one for the same input
+    pub fn bind(&mut self, binding: Binding) {
+        let existing = self
+            .bindings
+            .iter_mut()
+            .find(|b| b.key == binding.key && b.type_ == binding.type_);
+        match existing {
+            Some(existing) => *existing = binding,
+            None => self.bindings.push(binding),
+        }
+    }
+
+    pub fn get(&self, key: InputKey, type_: InputType) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|b| b.key == key && b.type_ == type_)
+            .map(|b| b.action)
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// Step sent in place of `Repeat` binding of `key` once repeating speeds
+    /// up, like seeking by 10s instead of 5s. Without one, `Repeat` binding
+    /// is just sent more often.
+    pub fn fast_step(&self, key: InputKey) -> Option<Action> {
+        self.fast_steps
+            .iter()
+            .find(|&&(k, _)| k == key)
+            .map(|&(_, action)| action)
+    }
+
+    /// Adds pattern binding, replacing previous one for the same pattern
+    pub fn bind_pattern(&mut self, pattern: Pattern, action: Action) {
+        match self.patterns.iter_mut().find(|(p, _)| *p == pattern) {
+            Some(existing) => existing.1 = action,
+            None => self.patterns.push((pattern, action)),
+        }
+    }
+
+    pub fn get_pattern(&self, pattern: Pattern) -> Option<Action> {
+        self.patterns
+            .iter()
+            .find(|(p, _)| *p == pattern)
+            .map(|&(_, action)| action)
+    }
+
+    pub fn set_fast_step(&mut self, key: InputKey, action: Option<Action>) {
+        self.fast_steps.retain(|&(k, _)| k != key);
+        if let Some(action) = action {
+            self.fast_steps.push((key, action));
+        }
+    }
+
+    /// Maps input event in given mode to actions, in the order they should
+    /// happen
+    pub fn map(
+        &self,
+        key: InputKey,
+        type_: InputType,
+        mode: Mode,
+    ) -> Vec<Action> {
+        if let (InputKey::Back, InputType::Long) = (key, type_) {
+            return vec![Action::OpenMenu];
+        }
+        match mode {
+            Mode::Basic => self.map_basic(key, type_).into_iter().collect(),
+            Mode::Mouse => map_mouse(key, type_).into_iter().collect(),
+            Mode::Scroll => map_scroll(key, type_).into_iter().collect(),
+            Mode::AirMouse => map_air_mouse(key, type_).into_iter().collect(),
+        }
+    }
+
+    fn map_basic(&self, key: InputKey, type_: InputType) -> Option<Action> {
+        match (key, type_) {
+            (InputKey::Back, InputType::Short) => {
+                Some(Action::SwitchMode(Mode::Mouse))
+            }
+            _ => self.get(key, type_),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    MissingEquals,
+    UnknownInputKey,
+    UnknownInputType,
+    ReservedInputKey,
+    UnknownKey,
+    UnknownModifier,
+    /// Consumer keys can't be combined with modifiers
+    ModifiedConsumerKey,
+    /// Only `Repeat` bindings can have fast step
+    UnexpectedFastStep,
+    /// Chord of input with itself
+    InvalidChord,
+}
+
+fn parse_binding(input: &str, action: &str) -> Result<Binding, ParseErrorKind> {
+    let mut input = input.split_whitespace();
+    let key = input
+        .next()
+        .and_then(input_key_from_name)
+        .ok_or(ParseErrorKind::UnknownInputKey)?;
+    let type_ = input
+        .next()
+        .and_then(input_type_from_name)
+        .ok_or(ParseErrorKind::UnknownInputType)?;
+    if input.next().is_some() {
+        return Err(ParseErrorKind::UnknownInputType);
+    }
+    if key == InputKey::Back {
+        return Err(ParseErrorKind::ReservedInputKey);
+    }
+    Ok(Binding {
+        key,
+        type_,
+        action: parse_action(action)?,
+    })
+}
+
+/// Parses pattern side of binding, `None` if it binds plain input type
+fn parse_pattern(input: &str) -> Result<Option<Pattern>, ParseErrorKind> {
+    let input_key = |name| match input_key_from_name(name) {
+        Some(InputKey::Back) => Err(ParseErrorKind::ReservedInputKey),
+        Some(key) => Ok(key),
+        None => Err(ParseErrorKind::UnknownInputKey),
+    };
+    let mut parts = input.split_whitespace();
+    let key = parts.next().unwrap_or_default();
+    let type_ = parts.next();
+    if parts.next().is_some() {
+        return Ok(None);
+    }
+    if let Some((a, b)) = key.split_once('+') {
+        if type_.is_some() {
+            return Err(ParseErrorKind::UnknownInputType);
+        }
+        let (a, b) = (input_key(a)?, input_key(b)?);
+        if a == b {
+            return Err(ParseErrorKind::InvalidChord);
+        }
+        return Ok(Some(Pattern::Chord(a, b)));
+    }
+    let pattern: fn(InputKey) -> Pattern = match type_ {
+        Some(t) if t.eq_ignore_ascii_case("Double") => Pattern::DoubleTap,
+        Some(t) if t.eq_ignore_ascii_case("Triple") => Pattern::TripleTap,
+        Some(t) if t.eq_ignore_ascii_case("VeryLong") => Pattern::VeryLong,
+        _ => return Ok(None),
+    };
+    Ok(Some(pattern(input_key(key)?)))
+}
+
+fn parse_action(action: &str) -> Result<Action, ParseErrorKind> {
+    let mut parts = action.rsplit('+').map(str::trim);
+    let name = parts.next().unwrap_or_default();
+    let mut mods = KeyMods::empty();
+    for part in parts {
+        mods |= KeyMods::from_name_lenient(part)
+            .ok_or(ParseErrorKind::UnknownModifier)?;
+    }
+    if let Some(key) = Key::from_name(name) {
+        Ok(Action::KeyTap(key | mods))
+    } else if let Ok(key) = name.parse::<ConsumerKey>() {
+        if !mods.is_empty() {
+            return Err(ParseErrorKind::ModifiedConsumerKey);
+        }
+        Ok(Action::ConsumerTap(key))
+    } else {
+        Err(ParseErrorKind::UnknownKey)
+    }
+}
+
+fn input_key_from_name(name: &str) -> Option<InputKey> {
+    [
+        ("Up", InputKey::Up),
+        ("Down", InputKey::Down),
+        ("Left", InputKey::Left),
+        ("Right", InputKey::Right),
+        ("Ok", InputKey::Ok),
+        ("Back", InputKey::Back),
+    ]
+    .into_iter()
+    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+    .map(|(_, key)| key)
+}
+
+fn input_type_from_name(name: &str) -> Option<InputType> {
+    [
+        ("Press", InputType::Press),
+        ("Release", InputType::Release),
+        ("Short", InputType::Short),
+        ("Long", InputType::Long),
+        ("Repeat", InputType::Repeat),
+    ]
+    .into_iter()
+    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+    .map(|(_, type_)| type_)
+}
+
+/// Patterns of Mouse mode, on top of its plain bindings: double tap of Ok
+/// toggles drag lock, and holding it very long middle-clicks
+pub fn mouse_patterns() -> Keymap {
+    let mut keymap = Keymap::from_table(&[]);
+    keymap
+        .bind_pattern(Pattern::DoubleTap(InputKey::Ok), Action::ToggleDragLock);
+    keymap.bind_pattern(
+        Pattern::VeryLong(InputKey::Ok),
+        Action::MouseClick(MouseButton::M3),
+    );
+    keymap
+}
+
+fn map_mouse(key: InputKey, type_: InputType) -> Option<Action> {
+    let action = match (key, type_) {
+        (InputKey::Back, InputType::Short) => Action::SwitchMode(Mode::Scroll),
+        (InputKey::Ok, InputType::Short) => Action::MouseClick(MouseButton::M1),
+        (InputKey::Ok, InputType::Long) => Action::MouseClick(MouseButton::M2),
+        (key, InputType::Press) => Action::StartMove(direction(key)?),
+        (key, InputType::Release) => Action::StopMove(direction(key)?),
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn map_scroll(key: InputKey, type_: InputType) -> Option<Action> {
+    let action = match (key, type_) {
+        (InputKey::Back, InputType::Short) => {
+            Action::SwitchMode(Mode::AirMouse)
+        }
+        (InputKey::Up | InputKey::Down, InputType::Press) => {
+            Action::StartScroll(direction(key)?)
+        }
+        (InputKey::Up | InputKey::Down, InputType::Release) => {
+            Action::StopScroll(direction(key)?)
+        }
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn map_air_mouse(key: InputKey, type_: InputType) -> Option<Action> {
+    let action = match (key, type_) {
+        (InputKey::Back, InputType::Short) => Action::SwitchMode(Mode::Basic),
+        (InputKey::Ok, InputType::Short) => Action::MouseClick(MouseButton::M1),
+        (InputKey::Ok, InputType::Long) => Action::Recenter,
+        (InputKey::Left, InputType::Short) => {
+            Action::MouseClick(MouseButton::M2)
+        }
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn direction(key: InputKey) -> Option<Direction> {
+    match key {
+        InputKey::Up => Some(Direction::Up),
+        InputKey::Down => Some(Direction::Down),
+        InputKey::Left => Some(Direction::Left),
+        InputKey::Right => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::{PatternConfig, PatternRecognizer, Trigger};
+
+    fn map(key: InputKey, type_: InputType, mode: Mode) -> Vec<Action> {
+        Keymap::builtin().map(key, type_, mode)
+    }
+
+    #[test]
+    fn builtin_basic_bindings() {
+        let shift = KeyMods::LeftShift;
+        let key = |key: Key| Action::KeyTap(key.into());
+        let expected = [
+            (InputKey::Ok, InputType::Short, key(Key::Spacebar)),
+            (InputKey::Ok, InputType::Long, key(Key::F)),
+            (InputKey::Left, InputType::Short, key(Key::LeftArrow)),
+            (InputKey::Right, InputType::Short, key(Key::RightArrow)),
+            (
+                InputKey::Left,
+                InputType::Long,
+                Action::KeyTap(Key::Comma | shift),
+            ),
+            (
+                InputKey::Right,
+                InputType::Long,
+                Action::KeyTap(Key::Dot | shift),
+            ),
+            (InputKey::Up, InputType::Short, key(Key::Dot)),
+            (InputKey::Down, InputType::Short, key(Key::Comma)),
+            (
+                InputKey::Up,
+                InputType::Long,
+                Action::ConsumerTap(ConsumerKey::VolumeIncrease),
+            ),
+            (
+                InputKey::Down,
+                InputType::Long,
+                Action::ConsumerTap(ConsumerKey::VolumeDecrease),
+            ),
+            (InputKey::Left, InputType::Repeat, key(Key::LeftArrow)),
+            (InputKey::Right, InputType::Repeat, key(Key::RightArrow)),
+            (
+                InputKey::Up,
+                InputType::Repeat,
+                Action::ConsumerTap(ConsumerKey::VolumeIncrease),
+            ),
+            (
+                InputKey::Down,
+                InputType::Repeat,
+                Action::ConsumerTap(ConsumerKey::VolumeDecrease),
+            ),
+        ];
+        for (input, type_, action) in expected {
+            assert_eq!(
+                map(input, type_, Mode::Basic),
+                [action],
+                "{input:?} {type_:?}",
+            );
+        }
+        assert_eq!(Keymap::builtin().bindings().len(), expected.len());
+    }
+
+    #[test]
+    fn builtin_fast_steps() {
+        let keymap = Keymap::builtin();
+        let key = |key: Key| Some(Action::KeyTap(key.into()));
+        assert_eq!(keymap.fast_step(InputKey::Left), key(Key::J));
+        assert_eq!(keymap.fast_step(InputKey::Right), key(Key::L));
+        assert_eq!(keymap.fast_step(InputKey::Up), None);
+    }
+
+    #[test]
+    fn press_and_release_unbound_in_basic() {
+        for type_ in [InputType::Press, InputType::Release] {
+            assert_eq!(map(InputKey::Ok, type_, Mode::Basic), []);
+        }
+    }
+
+    #[test]
+    fn back_short_cycles_modes() {
+        let modes = [Mode::Basic, Mode::Mouse, Mode::Scroll, Mode::AirMouse];
+        for (i, mode) in modes.into_iter().enumerate() {
+            let next = modes[(i + 1) % modes.len()];
+            assert_eq!(
+                map(InputKey::Back, InputType::Short, mode),
+                [Action::SwitchMode(next)],
+            );
+        }
+    }
+
+    #[test]
+    fn back_long_opens_menu_in_every_mode() {
+        for mode in [Mode::Basic, Mode::Mouse, Mode::Scroll, Mode::AirMouse] {
+            assert_eq!(
+                map(InputKey::Back, InputType::Long, mode),
+                [Action::OpenMenu],
+            );
+        }
+    }
+
+    fn parse_error(text: &str) -> ParseError {
+        Keymap::parse(text).unwrap_err()
+    }
+
+    #[test]
+    fn parse_skips_comments_and_blank_lines() {
+        let keymap = Keymap::parse(
+            "# full line comment\n\n   \nOk Long = M # trailing comment\n",
+        )
+        .unwrap();
+        assert_eq!(
+            keymap.get(InputKey::Ok, InputType::Long),
+            Some(Action::KeyTap(Key::M.into())),
+        );
+        assert_eq!(Keymap::parse("# nothing\n\n").unwrap(), Keymap::builtin());
+    }
+
+    #[test]
+    fn parse_keeps_unmentioned_builtin_bindings() {
+        let keymap = Keymap::parse("Ok Long = M").unwrap();
+        assert_eq!(
+            keymap.get(InputKey::Ok, InputType::Short),
+            Some(Action::KeyTap(Key::Spacebar.into())),
+        );
+    }
+
+    #[test]
+    fn parse_modifiers() {
+        let keymap = Keymap::parse(
+            "Left Long = Shift+Comma\nRight Long = ctrl + alt + t",
+        )
+        .unwrap();
+        assert_eq!(
+            keymap.get(InputKey::Left, InputType::Long),
+            Some(Action::KeyTap(Key::Comma | KeyMods::LeftShift)),
+        );
+        assert_eq!(
+            keymap.get(InputKey::Right, InputType::Long),
+            Some(Action::KeyTap(
+                Key::T | KeyMods::LeftCtrl | KeyMods::LeftAlt
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_consumer_keys_by_name_and_hex_id() {
+        let keymap = Keymap::parse(
+            "Up Long = VolumeIncrease\nDown Long = 0x224\nOk Long = 0X3ff",
+        )
+        .unwrap();
+        assert_eq!(
+            keymap.get(InputKey::Up, InputType::Long),
+            Some(Action::ConsumerTap(ConsumerKey::VolumeIncrease)),
+        );
+        assert_eq!(
+            keymap.get(InputKey::Down, InputType::Long),
+            Some(Action::ConsumerTap(ConsumerKey::AcBack)),
+        );
+        assert_eq!(
+            keymap.get(InputKey::Ok, InputType::Long),
+            Some(Action::ConsumerTap(ConsumerKey::Other(0x3FF))),
+        );
+    }
+
+    #[test]
+    fn parse_fast_step() {
+        let keymap = Keymap::parse("Left Repeat = LeftArrow, J").unwrap();
+        assert_eq!(
+            keymap.fast_step(InputKey::Left),
+            Some(Action::KeyTap(Key::J.into())),
+        );
+        let keymap = Keymap::parse("Left Repeat = LeftArrow").unwrap();
+        assert_eq!(keymap.fast_step(InputKey::Left), None);
+    }
+
+    #[test]
+    fn parse_patterns() {
+        let keymap = Keymap::parse(
+            "Ok Double = F\nOk Triple = M\nUp+Down = C\nOk VeryLong = K",
+        )
+        .unwrap();
+        let key = |key: Key| Some(Action::KeyTap(key.into()));
+        assert_eq!(
+            keymap.get_pattern(Pattern::DoubleTap(InputKey::Ok)),
+            key(Key::F)
+        );
+        assert_eq!(
+            keymap.get_pattern(Pattern::TripleTap(InputKey::Ok)),
+            key(Key::M)
+        );
+        assert_eq!(
+            keymap.get_pattern(Pattern::Chord(InputKey::Down, InputKey::Up)),
+            key(Key::C)
+        );
+        assert_eq!(
+            keymap.get_pattern(Pattern::VeryLong(InputKey::Ok)),
+            key(Key::K)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = [
+            ("Ok Long F", ParseErrorKind::MissingEquals),
+            ("Middle Long = F", ParseErrorKind::UnknownInputKey),
+            ("Ok Longer = F", ParseErrorKind::UnknownInputType),
+            ("Ok = F", ParseErrorKind::UnknownInputType),
+            ("Back Short = F", ParseErrorKind::ReservedInputKey),
+            ("Back Double = F", ParseErrorKind::ReservedInputKey),
+            ("Ok+Back = F", ParseErrorKind::ReservedInputKey),
+            ("Ok Long = Nope", ParseErrorKind::UnknownKey),
+            ("Ok Long = Hyper+F", ParseErrorKind::UnknownModifier),
+            (
+                "Ok Long = Shift+PlayPause",
+                ParseErrorKind::ModifiedConsumerKey,
+            ),
+            ("Ok Long = F, J", ParseErrorKind::UnexpectedFastStep),
+            ("Ok+Ok = F", ParseErrorKind::InvalidChord),
+            ("up+UP = F", ParseErrorKind::InvalidChord),
+        ];
+        for (text, kind) in cases {
+            assert_eq!(
+                parse_error(text),
+                ParseError { line: 1, kind },
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_error_reports_line() {
+        let text =
+            "# comment\n\nOk Long = F\nLeft Long = Nope\nOk Short = Space";
+        assert_eq!(
+            parse_error(text),
+            ParseError {
+                line: 4,
+                kind: ParseErrorKind::UnknownKey
+            },
+        );
+    }
+
+    #[test]
+    fn mouse_buttons() {
+        assert_eq!(
+            map(InputKey::Ok, InputType::Short, Mode::Mouse),
+            [Action::MouseClick(MouseButton::M1)],
+        );
+        assert_eq!(
+            map(InputKey::Ok, InputType::Long, Mode::Mouse),
+            [Action::MouseClick(MouseButton::M2)],
+        );
+        let patterns = mouse_patterns();
+        assert_eq!(
+            patterns.get_pattern(Pattern::DoubleTap(InputKey::Ok)),
+            Some(Action::ToggleDragLock),
+        );
+        assert_eq!(
+            patterns.get_pattern(Pattern::VeryLong(InputKey::Ok)),
+            Some(Action::MouseClick(MouseButton::M3)),
+        );
+    }
+
+    /// Mouse mode actions of Ok events, at given times, ticked every 10 ms
+    /// until `end_ms`
+    fn mouse_ok(events: &[(u32, InputType)], end_ms: u32) -> Vec<Action> {
+        let (keymap, patterns) = (Keymap::builtin(), mouse_patterns());
+        let mut recognizer = PatternRecognizer::new(PatternConfig::default());
+        let mut triggers = Vec::new();
+        let mut events = events.iter().peekable();
+        for now in (0..=end_ms).step_by(10) {
+            while let Some((_, type_)) = events.next_if(|(t, _)| *t <= now) {
+                triggers.extend(recognizer.on_input(
+                    &patterns,
+                    InputKey::Ok,
+                    *type_,
+                    now,
+                ));
+            }
+            triggers.extend(recognizer.tick(&patterns, now));
+        }
+        triggers
+            .into_iter()
+            .flat_map(|trigger| match trigger {
+                Trigger::Input(key, type_) => {
+                    keymap.map(key, type_, Mode::Mouse)
+                }
+                Trigger::Pattern(p) => {
+                    patterns.get_pattern(p).into_iter().collect()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mouse_ok_gestures() {
+        use InputType::*;
+
+        let click = |button| [Action::MouseClick(button)];
+        let tap = [(0, Press), (80, Release), (80, Short)];
+        assert_eq!(mouse_ok(&tap, 1000), click(MouseButton::M1));
+        let double_tap =
+            [tap, [(150, Press), (230, Release), (230, Short)]].concat();
+        assert_eq!(mouse_ok(&double_tap, 1000), [Action::ToggleDragLock]);
+        let long = [(0, Press), (500, Long), (800, Release)];
+        assert_eq!(mouse_ok(&long, 3000), click(MouseButton::M2));
+        let very_long = [(0, Press), (500, Long), (2500, Release)];
+        assert_eq!(mouse_ok(&very_long, 3000), click(MouseButton::M3));
+    }
+
+    #[test]
+    fn mouse_moves_while_direction_held() {
+        assert_eq!(
+            map(InputKey::Left, InputType::Press, Mode::Mouse),
+            [Action::StartMove(Direction::Left)],
+        );
+        assert_eq!(
+            map(InputKey::Left, InputType::Release, Mode::Mouse),
+            [Action::StopMove(Direction::Left)],
+        );
+    }
+
+    #[test]
+    fn scroll_mode_only_scrolls() {
+        assert_eq!(
+            map(InputKey::Up, InputType::Press, Mode::Scroll),
+            [Action::StartScroll(Direction::Up)],
+        );
+        assert_eq!(
+            map(InputKey::Down, InputType::Release, Mode::Scroll),
+            [Action::StopScroll(Direction::Down)],
+        );
+        for type_ in [InputType::Short, InputType::Long] {
+            assert_eq!(map(InputKey::Ok, type_, Mode::Scroll), []);
+        }
+        assert_eq!(map(InputKey::Left, InputType::Press, Mode::Scroll), []);
+    }
+
+    #[test]
+    fn back_long_ignores_custom_bindings() {
+        let mut keymap = Keymap::builtin();
+        keymap.bind(Binding {
+            key: InputKey::Back,
+            type_: InputType::Long,
+            action: Action::KeyTap(Key::Escape.into()),
+        });
+        assert_eq!(
+            keymap.map(InputKey::Back, InputType::Long, Mode::Basic),
+            [Action::OpenMenu],
+        );
+    }
+}
@@ -1,10 +1,39 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 extern crate alloc;
 
+// Modules that talk to firmware only build for Flipper, the rest also builds
+// on host, to run tests:
+// cargo test --lib --target <host>
+
 pub mod bt;
+#[cfg(target_os = "none")]
+pub mod feedback;
+pub mod hid;
+pub mod hosts;
+#[cfg(target_os = "none")]
 pub mod icons;
 pub mod imu;
+pub mod keymap;
+pub mod macros;
+pub mod menu;
+pub mod mouse;
+pub mod pattern;
+pub mod profile;
+pub mod repeat;
+pub mod screen;
+#[cfg(target_os = "none")]
+pub mod screens;
+pub mod search;
+pub mod settings;
+#[cfg(target_os = "none")]
+pub mod storage;
+#[cfg(target_os = "none")]
+pub mod sync;
+pub mod typing;
+pub mod view_port;
+#[cfg(target_os = "none")]
+pub mod views;
 
 #[derive(Debug)]
 pub struct Error;
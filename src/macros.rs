@@ -0,0 +1,458 @@
+//! Recorded sequences of HID actions, replayed from menu
+
+use core::{ffi::CStr, fmt, str::FromStr};
+
+use alloc::{ffi::CString, format, string::String, vec::Vec};
+
+#[cfg(target_os = "none")]
+use crate::storage::{APP_DATA_DIR, Storage};
+use crate::{Error, hid::HidSink, keymap::Action};
+
+/// Path of saved macros, see [`Macros::parse`] for format
+pub const MACROS_PATH: &CStr = c"/ext/apps_data/yt_remote/macros.txt";
+
+/// Longest pause kept between recorded steps, in ms, so that leaving
+/// recording on for a while doesn't make replay hang
+pub const MAX_DELAY_MS: u32 = 10_000;
+
+/// Longest name that can be given to recorded macro
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Single recorded action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    delay_ms: u32,
+    action: Action,
+}
+
+impl Step {
+    /// Gives `None` for actions that don't send HID reports by themselves,
+    /// and so can't be replayed
+    pub fn new(delay_ms: u32, action: Action) -> Option<Self> {
+        match action {
+            Action::KeyTap(_)
+            | Action::ConsumerTap(_)
+            | Action::MouseClick(_)
+            | Action::MousePress(_)
+            | Action::MouseRelease(_)
+            | Action::MouseMove(..)
+            | Action::MouseScroll(_) => Some(Self { delay_ms, action }),
+            _ => None,
+        }
+    }
+
+    /// Time since previous step, in ms
+    pub fn delay_ms(&self) -> u32 {
+        self.delay_ms
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+}
+
+/// Writes step as single line of macros file
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.delay_ms)?;
+        match self.action {
+            Action::KeyTap(combo) => write!(f, "Key {combo}"),
+            Action::ConsumerTap(key) => write!(f, "Consumer {key}"),
+            Action::MouseClick(button) => write!(f, "Click {button}"),
+            Action::MousePress(button) => write!(f, "Press {button}"),
+            Action::MouseRelease(button) => write!(f, "Release {button}"),
+            Action::MouseMove(dx, dy) => write!(f, "Move {dx} {dy}"),
+            Action::MouseScroll(delta) => write!(f, "Scroll {delta}"),
+            // rejected by `Step::new`
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl FromStr for Step {
+    type Err = ParseErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let delay_ms = parts
+            .next()
+            .and_then(|delay| delay.parse().ok())
+            .ok_or(ParseErrorKind::InvalidDelay)?;
+        let kind = parts.next().unwrap_or_default();
+        let args: Vec<&str> = parts.collect();
+        parse_action(kind, &args)
+            .and_then(|action| Self::new(delay_ms, action))
+            .ok_or(ParseErrorKind::InvalidAction)
+    }
+}
+
+fn parse_action(kind: &str, args: &[&str]) -> Option<Action> {
+    let is = |name: &str| kind.eq_ignore_ascii_case(name);
+    let action = match *args {
+        [arg] if is("Key") => Action::KeyTap(arg.parse().ok()?),
+        [arg] if is("Consumer") => Action::ConsumerTap(arg.parse().ok()?),
+        [arg] if is("Click") => Action::MouseClick(arg.parse().ok()?),
+        [arg] if is("Press") => Action::MousePress(arg.parse().ok()?),
+        [arg] if is("Release") => Action::MouseRelease(arg.parse().ok()?),
+        [dx, dy] if is("Move") => {
+            Action::MouseMove(dx.parse().ok()?, dy.parse().ok()?)
+        }
+        [delta] if is("Scroll") => Action::MouseScroll(delta.parse().ok()?),
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Macro {
+    pub name: CString,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayError {
+    /// Stopped before sending all steps
+    Cancelled,
+    Hid,
+}
+
+impl From<Error> for PlayError {
+    fn from(_: Error) -> Self {
+        PlayError::Hid
+    }
+}
+
+impl Macro {
+    /// Total time replay takes, in ms
+    pub fn duration_ms(&self) -> u32 {
+        self.steps.iter().map(|step| step.delay_ms).sum()
+    }
+
+    /// Replays steps into host.
+    ///
+    /// `wait` is called with delay of every step before sending it, and
+    /// should wait that long. Returning `false` from it cancels replay,
+    /// releasing any mouse buttons macro pressed.
+    pub fn play(
+        &self,
+        hid: &impl HidSink,
+        mut wait: impl FnMut(u32) -> bool,
+    ) -> Result<(), PlayError> {
+        for step in &self.steps {
+            if !wait(step.delay_ms) {
+                hid.mouse_release_all()?;
+                return Err(PlayError::Cancelled);
+            }
+            step.action.send(hid)?;
+        }
+        Ok(())
+    }
+}
+
+/// Captures actions sent to host, along with time between them
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    steps: Vec<Step>,
+    last_ms: Option<u32>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `action` done at `now_ms`, returning whether it could be.
+    /// First step has no delay.
+    pub fn record(&mut self, action: Action, now_ms: u32) -> bool {
+        let delay_ms = self
+            .last_ms
+            .map_or(0, |last| now_ms.wrapping_sub(last).min(MAX_DELAY_MS));
+        let Some(step) = Step::new(delay_ms, action) else {
+            return false;
+        };
+        self.steps.push(step);
+        self.last_ms = Some(now_ms);
+        true
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn finish(self, name: CString) -> Macro {
+        Macro {
+            name,
+            steps: self.steps,
+        }
+    }
+}
+
+/// All saved macros, in order they were added
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Macros {
+    macros: Vec<Macro>,
+}
+
+impl Macros {
+    /// Parses macros file.
+    ///
+    /// Each macro starts with its name in brackets, followed by its steps,
+    /// one per line. Step is delay since previous one in ms, and action:
+    /// ```text
+    /// # comment
+    /// [Fullscreen with captions]
+    /// 0 Key F
+    /// 150 Key C
+    /// 150 Key LeftShift+Dot
+    /// 0 Consumer VolumeIncrease
+    /// 500 Click M1
+    /// 20 Move 10 -5
+    /// 10 Scroll -1
+    /// ```
+    /// Keys are written like in keymap file, except that consumer keys are
+    /// never taken for keyboard ones. `Press` and `Release` hold and let go
+    /// of mouse button, `Scroll` turns wheel by notches, positive up.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut macros = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let error = |kind| ParseError { line: i + 1, kind };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[') {
+                let name = name
+                    .strip_suffix(']')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .and_then(|name| CString::new(name).ok())
+                    .ok_or(error(ParseErrorKind::InvalidName))?;
+                macros.macros.push(Macro {
+                    name,
+                    steps: Vec::new(),
+                });
+                continue;
+            }
+            let step = line.parse().map_err(error)?;
+            macros
+                .macros
+                .last_mut()
+                .ok_or(error(ParseErrorKind::StepOutsideMacro))?
+                .steps
+                .push(step);
+        }
+        Ok(macros)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (i, macro_) in self.macros.iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            text.push('[');
+            text.push_str(&macro_.name.to_string_lossy());
+            text.push_str("]\n");
+            for step in &macro_.steps {
+                text.push_str(&format!("{step}\n"));
+            }
+        }
+        text
+    }
+
+    #[cfg(target_os = "none")]
+    pub fn save(&self, storage: &Storage) -> Result<(), Error> {
+        storage.mkdir(APP_DATA_DIR)?;
+        storage.write(MACROS_PATH, self.to_text().as_bytes())
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Macro> {
+        self.macros.get(index)
+    }
+
+    pub fn macros(&self) -> &[Macro] {
+        &self.macros
+    }
+
+    pub fn names(&self) -> Vec<&CStr> {
+        self.macros.iter().map(|m| m.name.as_c_str()).collect()
+    }
+
+    /// Adds macro, replacing previous one with the same name
+    pub fn add(&mut self, macro_: Macro) {
+        match self.macros.iter_mut().find(|m| m.name == macro_.name) {
+            Some(existing) => *existing = macro_,
+            None => self.macros.push(macro_),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Macro> {
+        (index < self.macros.len()).then(|| self.macros.remove(index))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// Name in brackets is empty or unterminated
+    InvalidName,
+    /// Step came before any macro name
+    StepOutsideMacro,
+    InvalidDelay,
+    InvalidAction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bt::{ConsumerKey, Key, KeyCombo, KeyMods, MouseButton},
+        hid::{HidEvent, RecordingHid},
+    };
+
+    const TEXT: &str = "\
+[Fullscreen]
+0 Key F
+
+[Captions then scroll]
+0 Key LeftShift+C
+150 Consumer VolumeIncrease
+500 Click M1
+20 Press M2
+20 Move 10 -5
+10 Scroll -1
+20 Release M2
+";
+
+    #[test]
+    fn parses_steps() {
+        let macros = Macros::parse(TEXT).unwrap();
+        assert_eq!(macros.names(), [c"Fullscreen", c"Captions then scroll"]);
+        let steps: Vec<_> = macros.macros()[1]
+            .steps
+            .iter()
+            .map(|s| (s.delay_ms(), s.action()))
+            .collect();
+        let shift_c = KeyCombo::new(Key::C, KeyMods::LeftShift);
+        assert_eq!(
+            steps,
+            [
+                (0, Action::KeyTap(shift_c)),
+                (150, Action::ConsumerTap(ConsumerKey::VolumeIncrease)),
+                (500, Action::MouseClick(MouseButton::M1)),
+                (20, Action::MousePress(MouseButton::M2)),
+                (20, Action::MouseMove(10, -5)),
+                (10, Action::MouseScroll(-1)),
+                (20, Action::MouseRelease(MouseButton::M2)),
+            ]
+        );
+        assert_eq!(macros.macros()[1].duration_ms(), 720);
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let macros = Macros::parse(TEXT).unwrap();
+        assert_eq!(macros.to_text(), TEXT);
+        assert_eq!(Macros::parse(&macros.to_text()), Ok(macros));
+    }
+
+    #[test]
+    fn comments_and_case() {
+        let macros =
+            Macros::parse("# mine\n[ A ] # first\n5 click m1\n").unwrap();
+        assert_eq!(macros.names(), [c"A"]);
+        assert_eq!(
+            macros.macros()[0].steps[0].action(),
+            Action::MouseClick(MouseButton::M1)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |text, line, kind| {
+            assert_eq!(Macros::parse(text), Err(ParseError { line, kind }));
+        };
+        error("[]", 1, ParseErrorKind::InvalidName);
+        error("[A", 1, ParseErrorKind::InvalidName);
+        error("0 Key F", 1, ParseErrorKind::StepOutsideMacro);
+        error("[A]\n\nx Key F", 3, ParseErrorKind::InvalidDelay);
+        error("[A]\n-1 Key F", 2, ParseErrorKind::InvalidDelay);
+        error("[A]\n0 Key", 2, ParseErrorKind::InvalidAction);
+        error("[A]\n0 Move 1", 2, ParseErrorKind::InvalidAction);
+        error("[A]\n0 Scroll 200", 2, ParseErrorKind::InvalidAction);
+        error("[A]\n0 Wait 1", 2, ParseErrorKind::InvalidAction);
+    }
+
+    #[test]
+    fn only_hid_actions_are_steps() {
+        assert!(Step::new(0, Action::ToggleDragLock).is_none());
+        assert!(Step::new(0, Action::OpenMenu).is_none());
+        assert!(Step::new(0, Action::MouseScroll(1)).is_some());
+    }
+
+    #[test]
+    fn recorder_measures_delays() {
+        let mut recorder = Recorder::new();
+        let f = Action::KeyTap(Key::F.into());
+        assert!(recorder.record(f, 1000));
+        assert!(!recorder.record(Action::Recenter, 1100));
+        assert!(recorder.record(Action::MouseMove(1, 1), 1250));
+        assert!(recorder.record(f, 1250 + MAX_DELAY_MS + 1));
+        let delays: Vec<_> =
+            recorder.steps().iter().map(Step::delay_ms).collect();
+        assert_eq!(delays, [0, 250, MAX_DELAY_MS]);
+        let macro_ = recorder.finish(c"F".into());
+        assert_eq!(macro_.steps.len(), 3);
+    }
+
+    #[test]
+    fn play_waits_then_sends() {
+        let macros = Macros::parse(TEXT).unwrap();
+        let hid = RecordingHid::new();
+        let mut waits = Vec::new();
+        let res = macros.macros()[1].play(&hid, |delay_ms| {
+            waits.push(delay_ms);
+            true
+        });
+        assert_eq!(res, Ok(()));
+        assert_eq!(waits, [0, 150, 500, 20, 20, 10, 20]);
+        let events = hid.take();
+        assert_eq!(events.len(), 10);
+        assert_eq!(
+            events[6..],
+            [
+                HidEvent::MousePress(MouseButton::M2),
+                HidEvent::MouseMove(10, -5),
+                HidEvent::MouseScroll(-1),
+                HidEvent::MouseRelease(MouseButton::M2),
+            ]
+        );
+    }
+
+    #[test]
+    fn cancel_releases_mouse_buttons() {
+        let macros = Macros::parse(TEXT).unwrap();
+        let hid = RecordingHid::new();
+        // cancelled while waiting to move with M2 held
+        let mut waits = 0;
+        let res = macros.macros()[1].play(&hid, |_| {
+            waits += 1;
+            waits < 5
+        });
+        assert_eq!(res, Err(PlayError::Cancelled));
+        let events = hid.take();
+        assert_eq!(
+            events[events.len() - 2..],
+            [
+                HidEvent::MousePress(MouseButton::M2),
+                HidEvent::MouseReleaseAll,
+            ]
+        );
+    }
+}
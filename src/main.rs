@@ -10,14 +10,18 @@ extern crate flipperzero_alloc;
 
 use core::{
     ffi::CStr,
-    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+    mem,
+    sync::atomic::{AtomicU8, Ordering},
 };
 
+use alloc::ffi::CString;
+
 use flipperzero::{
     furi::{message_queue::MessageQueue, time::FuriDuration},
     gui::{
         Gui,
-        view_port::{InputEvent, InputKey, InputType, Orientation, ViewPort},
+        canvas::Font,
+        view_port::{InputEvent, InputType, ViewPort},
     },
     println,
 };
@@ -25,8 +29,21 @@ use flipperzero_rt::{entry, manifest};
 use flipperzero_sys as sys;
 
 use flipper_yt_remote::{
-    bt::{Bt, BtStatus, ConsumerKey, Key, KeyMods, MouseButton},
-    icons,
+    Error,
+    bt::{BleProfileBase, Bt, BtStatus, HidProfileParams},
+    feedback::Feedback,
+    hid::HidSink,
+    hosts, icons,
+    imu::icm42688::Icm42688,
+    keymap::{self, Keymap},
+    macros::{self, Macros},
+    profile::{Profile, Profiles},
+    screen::{App, Navigator},
+    screens::{self, BasicScreen},
+    search::History,
+    settings::Settings,
+    storage::Storage,
+    sync::TryLock,
 };
 
 manifest!(
@@ -38,8 +55,8 @@ manifest!(
 
 struct State {
     event_queue: MessageQueue<InputEvent>,
-    bt_connected: AtomicBool,
-    mode: AtomicU8,
+    /// Raw [`BtStatus`]
+    bt_status: AtomicU8,
 }
 
 entry!(main);
@@ -48,64 +65,95 @@ fn main(_args: Option<&CStr>) -> i32 {
 
     let state = State {
         event_queue: MessageQueue::new(8),
-        bt_connected: AtomicBool::new(false),
-        mode: AtomicU8::new(0),
+        bt_status: AtomicU8::new(BtStatus::Off.into_raw().0),
     };
 
-    let mut bt = Bt::open();
+    let storage = Storage::open();
+    let mut profiles = Profiles::builtin();
+    if let Some(keymap) = load_keymap(&storage) {
+        profiles.add(Profile {
+            name: c"Custom",
+            keymap,
+        });
+    }
+    let settings = Settings::load(&storage);
+    let profile = settings
+        .profile
+        .as_deref()
+        .and_then(|name| CString::new(name).ok())
+        .and_then(|name| profiles.position(&name))
+        .or_else(|| profiles.position(c"Custom"))
+        .unwrap_or(0);
+    let mut hid_params = host_params(&settings, settings.host);
+    // last host profile started for, to fall back to
+    let mut active_host = settings.host;
+
+    let bt = Bt::open();
     bt.disconnect();
     unsafe { sys::furi_delay_ms(200) };
-    bt.set_key_storage_path(c"/data/.bt_hid.keys");
-    bt.set_status_changed_callback(|status| {
-        state
-            .bt_connected
-            .store(status == BtStatus::Connected, Ordering::Relaxed)
-    });
-    let bt_hid_profile = bt
-        .hid_profile_start(sys::BleProfileHidParams {
-            device_name_prefix: c"YtRemote".as_ptr(),
-            mac_xor: 1,
+    let status_changed_guard = bt
+        .set_status_changed_callback(|status| {
+            state
+                .bt_status
+                .store(status.into_raw().0, Ordering::Relaxed)
         })
         .unwrap();
-    Bt::start_advertising();
+    let Ok(bt_hid_profile) = start_host(&bt, &hid_params, settings.host) else {
+        println!("starting HID profile failed\r");
+        drop(status_changed_guard);
+        bt.set_default_key_storage_path();
+        return 1;
+    };
+
+    let app = App {
+        events: &state.event_queue,
+        profiles,
+        profile,
+        // no sensor attached means no air mouse mode
+        imu: Icm42688::open().ok(),
+        gestures_enabled: false,
+        settings,
+        history: History::load(&storage),
+        macros: load_macros(&storage).unwrap_or_default(),
+        recorder: None,
+        switch_host: None,
+    };
+    let mut navigator = Navigator::new(app);
+    screens::register_all(&mut navigator);
+    navigator.start(BasicScreen::ID, &bt_hid_profile);
+    // `None` once no host profile could be started
+    let mut bt_hid_profile = Some(bt_hid_profile);
+
+    let mut orientation = navigator.app().settings.orientation;
+    let navigator = TryLock::new(navigator);
 
     let mut view_port = ViewPort::new();
-    view_port.set_orientation(Orientation::VerticalFlip);
+    view_port.set_orientation(orientation);
     view_port.set_draw_callback(|canvas| {
-        let bt_connected = state.bt_connected.load(Ordering::Relaxed);
-        let mode = match state.mode.load(Ordering::Relaxed) {
-            0 => Mode::Basic,
-            1 => Mode::Mouse,
-            _ => unreachable!(),
+        let bt_status = state.bt_status.load(Ordering::Relaxed);
+        let bt_status = BtStatus::from_raw(sys::BtStatus(bt_status));
+
+        let blink = unsafe { sys::furi_get_tick() } / 500 % 2 == 1;
+        let icon = match bt_status {
+            BtStatus::Connected => Some(&icons::BLE_CONNECTED),
+            // waiting for host to connect
+            BtStatus::Advertising if blink => None,
+            _ => Some(&icons::BLE_DISCONNECTED),
         };
+        if let Some(icon) = icon {
+            canvas.draw_icon(0, 0, icon);
+        }
+        canvas.set_font(Font::Secondary);
 
-        let icon = if bt_connected {
-            &icons::BLE_CONNECTED
-        } else {
-            &icons::BLE_DISCONNECTED
+        // main loop holds navigator for as long as it's busy, like when
+        // typing or replaying macro
+        let Some(navigator) = navigator.try_lock() else {
+            canvas.draw_str(2, 30, c"Busy...");
+            return;
         };
-        canvas.draw_icon(0, 0, icon);
-
-        match mode {
-            Mode::Basic => {
-                let (x, y) = (32, (128 + 15) / 2);
-                canvas.draw_icon(53, 2, &icons::BASIC_MODE);
-                canvas.draw_circle(x, y, 5);
-                canvas.draw_icon(x - 2, y - 2, &icons::PLAY_PAUSE);
-                canvas.draw_circle(x, y, 16);
-                canvas.draw_icon(x - 14, y - 3, &icons::LEFT_ARROW);
-                canvas.draw_icon(x + 7, y - 3, &icons::RIGHT_ARROW);
-                canvas.draw_icon(x - 4, y - 13, &icons::FRAME_LEFT);
-                canvas.draw_icon(x - 4, y + 7, &icons::FRAME_RIGHT);
-                canvas.draw_circle(x, y, 25);
-                canvas.draw_icon(x - 23, y - 2, &icons::SPEED_LEFT);
-                canvas.draw_icon(x + 18, y - 2, &icons::SPEED_RIGHT);
-                canvas.draw_icon(x - 3, y - 23, &icons::VOLUME_UP);
-                canvas.draw_icon(x - 3, y + 18, &icons::VOLUME_DOWN);
-            }
-            Mode::Mouse => {
-                canvas.draw_icon(54, 2, &icons::MOUSE_MODE);
-            }
+        navigator.draw(canvas);
+        if navigator.app().recorder.is_some() && blink {
+            canvas.draw_str(2, 124, c"REC");
         }
     });
     view_port.set_input_callback(|input| {
@@ -116,110 +164,117 @@ fn main(_args: Option<&CStr>) -> i32 {
     });
 
     let gui = Gui::open();
-    let view_port = gui.add_view_port(view_port, sys::GuiLayerFullscreen);
-
-    #[repr(u8)]
-    #[derive(Clone, Copy, PartialEq, Eq)]
-    enum Mode {
-        Basic = 0,
-        Mouse = 1,
-    }
-
-    let mut mode = Mode::Basic;
+    let mut view_port = gui.add_view_port(view_port, sys::GuiLayerFullscreen);
+    let feedback = Feedback::open();
 
-    // TODO: re-add unpairing
-    // TODO: mouse acceleration
     // TODO: better text align enum
     loop {
-        state.mode.store(mode as u8, Ordering::Relaxed);
-        if let Ok(event) =
-            state.event_queue.get(FuriDuration::from_secs(1) / 30)
-        {
-            if let (InputKey::Back, InputType::Long) = (event.key, event.type_)
-            {
-                break;
-            }
-
-            if let Mode::Basic = mode {
-                if let (InputKey::Back, InputType::Short) =
-                    (event.key, event.type_)
-                {
-                    mode = Mode::Mouse
-                }
-                let key = match (event.key, event.type_) {
-                    (InputKey::Ok, InputType::Short) => Some(Key::Spacebar),
-                    (InputKey::Ok, InputType::Long) => Some(Key::F),
-                    (InputKey::Left, InputType::Short) => Some(Key::LeftArrow),
-                    (InputKey::Right, InputType::Short) => {
-                        Some(Key::RightArrow)
-                    }
-                    (InputKey::Left, InputType::Long) => {
-                        Some(Key::Comma | KeyMods::LeftShift)
-                    }
-                    (InputKey::Right, InputType::Long) => {
-                        Some(Key::Dot | KeyMods::LeftShift)
-                    }
-                    (InputKey::Up, InputType::Short) => Some(Key::Dot),
-                    (InputKey::Down, InputType::Short) => Some(Key::Comma),
-                    _ => None,
-                };
-                if let Some(key) = key {
-                    let _ = bt_hid_profile.key_press(key);
-                    let _ = bt_hid_profile.key_release(key);
-                }
-                let consumer_key = match (event.key, event.type_) {
-                    (InputKey::Up, InputType::Long) => {
-                        Some(ConsumerKey::VolumeIncrease)
-                    }
-                    (InputKey::Down, InputType::Long) => {
-                        Some(ConsumerKey::VolumeDecrease)
-                    }
-                    _ => None,
-                };
-                if let Some(button) = consumer_key {
-                    let _ = bt_hid_profile.consumer_key_press(button);
-                    let _ = bt_hid_profile.consumer_key_release(button);
-                }
-            } else if let Mode::Mouse = mode {
-                match (event.key, event.type_) {
-                    (InputKey::Back, InputType::Short) => mode = Mode::Basic,
-                    (InputKey::Ok, InputType::Press) => {
-                        let _ = bt_hid_profile.mouse_press(MouseButton::M1);
-                    }
-                    (InputKey::Ok, InputType::Release) => {
-                        let _ = bt_hid_profile.mouse_release(MouseButton::M1);
-                    }
-                    _ => (),
-                }
-                let dv = match (event.key, event.type_) {
-                    (InputKey::Left, InputType::Press) => Some((-5, 0)),
-                    (InputKey::Right, InputType::Press) => Some((5, 0)),
-                    (InputKey::Up, InputType::Press) => Some((0, -5)),
-                    (InputKey::Down, InputType::Press) => Some((0, 5)),
-                    (InputKey::Left, InputType::Repeat) => Some((-20, 0)),
-                    (InputKey::Right, InputType::Repeat) => Some((20, 0)),
-                    (InputKey::Up, InputType::Repeat) => Some((0, -20)),
-                    (InputKey::Down, InputType::Repeat) => Some((0, 20)),
-                    _ => None,
-                };
-                if let Some((dx, dy)) = dv {
-                    let _ = bt_hid_profile.mouse_move(dx, dy);
+        let Some(hid) = &bt_hid_profile else {
+            break;
+        };
+        let event = state.event_queue.get(FuriDuration::from_secs(1) / 30);
+        let mut navigator = navigator.lock();
+        let running = match event {
+            Ok(event) => {
+                if event.type_ == InputType::Press {
+                    feedback.press(&navigator.app().settings);
                 }
-            } else {
-                unreachable!()
+                navigator.on_input(&event, hid)
             }
+            Err(_) => true,
+        } && navigator.on_tick(hid);
+        let new_orientation = navigator.app().settings.orientation;
+        if !running {
+            break;
+        }
+        if let Some(host) = navigator.app_mut().switch_host.take() {
+            bt.disconnect();
+            unsafe { sys::furi_delay_ms(200) };
+            // restores default profile, so that new one starts from scratch
+            drop(bt_hid_profile);
+            // identity is read again, as it might have been changed in
+            // settings
+            let settings = &mut navigator.app_mut().settings;
+            let previous =
+                mem::replace(&mut hid_params, host_params(settings, host));
+            bt_hid_profile = 'started: {
+                if let Ok(profile) = start_host(&bt, &hid_params, host) {
+                    break 'started Some(profile);
+                }
+                println!("host {}: starting HID profile failed\r", host + 1);
+                // back to host that worked, exiting if it doesn't anymore
+                hid_params = previous;
+                settings.host = active_host;
+                let _ = settings.save(&storage);
+                start_host(&bt, &hid_params, active_host).ok()
+            };
+            active_host = settings.host;
+        }
+        drop(navigator);
+        if new_orientation != orientation {
+            orientation = new_orientation;
+            view_port.set_orientation(orientation);
         }
-
         view_port.update();
     }
 
     view_port.set_enabled(false);
 
-    bt.unset_status_changed_callback();
+    if let Some(profile) = &bt_hid_profile {
+        let _ = profile.mouse_release_all();
+    }
+    drop(status_changed_guard);
     bt.disconnect();
     unsafe { sys::furi_delay_ms(200) };
     bt.set_default_key_storage_path();
-    bt_hid_profile.restore_default_profile().unwrap();
+    // without profile, default one was restored when last one was dropped
+    if let Some(profile) = bt_hid_profile {
+        profile.restore_default_profile().unwrap();
+    }
 
     0
 }
+
+/// Starts HID profile as host in slot `host`, with its keys and identity,
+/// and advertises it
+fn start_host<'a>(
+    bt: &'a Bt,
+    params: &'a HidProfileParams,
+    host: usize,
+) -> Result<BleProfileBase<'a>, Error> {
+    bt.set_key_storage_path(&hosts::key_storage_path(host));
+    let profile = bt.hid_profile_start(params)?;
+    Bt::start_advertising();
+    Ok(profile)
+}
+
+/// Identity host in slot `host` knows remote by
+fn host_params(settings: &Settings, host: usize) -> HidProfileParams {
+    HidProfileParams::new(&settings.device_name)
+        .unwrap_or_default()
+        .with_mac_xor(settings.hosts[host].mac_xor)
+}
+
+/// Loads user macros, if there are valid ones
+fn load_macros(storage: &Storage) -> Option<Macros> {
+    let text = storage.read_to_string(macros::MACROS_PATH).ok()?;
+    match Macros::parse(&text) {
+        Ok(macros) => Some(macros),
+        Err(err) => {
+            println!("macros.txt:{}: {:?}\r", err.line, err.kind);
+            None
+        }
+    }
+}
+
+/// Loads user keymap, if there is a valid one
+fn load_keymap(storage: &Storage) -> Option<Keymap> {
+    let text = storage.read_to_string(keymap::KEYMAP_PATH).ok()?;
+    match Keymap::parse(&text) {
+        Ok(keymap) => Some(keymap),
+        Err(err) => {
+            println!("keymap.txt:{}: {:?}\r", err.line, err.kind);
+            None
+        }
+    }
+}
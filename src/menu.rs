@@ -0,0 +1,147 @@
+use crate::view_port::{InputKey, InputType};
+
+/// Vertical list of items, navigated with Up/Down and wrapping around
+pub struct Menu<'a, T> {
+    items: &'a [T],
+    cursor: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuEvent<T> {
+    /// Ok pressed on item
+    Select(T),
+    /// Left/Right pressed on item
+    Adjust(T, isize),
+    /// Back pressed
+    Close,
+    /// Back held
+    Exit,
+}
+
+impl<'a, T: Copy> Menu<'a, T> {
+    pub fn new(items: &'a [T]) -> Self {
+        Self { items, cursor: 0 }
+    }
+
+    pub fn items(&self) -> &'a [T] {
+        self.items
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn selected(&self) -> Option<T> {
+        self.items.get(self.cursor).copied()
+    }
+
+    pub fn on_input(
+        &mut self,
+        key: InputKey,
+        type_: InputType,
+    ) -> Option<MenuEvent<T>> {
+        let len = self.items.len().max(1);
+        match (key, type_) {
+            (InputKey::Back, InputType::Short) => Some(MenuEvent::Close),
+            (InputKey::Back, InputType::Long) => Some(MenuEvent::Exit),
+            (InputKey::Up, InputType::Short | InputType::Repeat) => {
+                self.cursor = (self.cursor + len - 1) % len;
+                None
+            }
+            (InputKey::Down, InputType::Short | InputType::Repeat) => {
+                self.cursor = (self.cursor + 1) % len;
+                None
+            }
+            (InputKey::Left, InputType::Short | InputType::Repeat) => {
+                self.selected().map(|item| MenuEvent::Adjust(item, -1))
+            }
+            (InputKey::Right, InputType::Short | InputType::Repeat) => {
+                self.selected().map(|item| MenuEvent::Adjust(item, 1))
+            }
+            (InputKey::Ok, InputType::Short) => {
+                self.selected().map(MenuEvent::Select)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Handles input on yes/no prompt, returning answer once there is one
+pub fn confirm(key: InputKey, type_: InputType) -> Option<bool> {
+    match (key, type_) {
+        (InputKey::Ok, InputType::Short) => Some(true),
+        (InputKey::Back, InputType::Short) => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEMS: &[char] = &['a', 'b', 'c'];
+
+    #[test]
+    fn cursor_wraps_around() {
+        let mut menu = Menu::new(ITEMS);
+        assert_eq!(menu.on_input(InputKey::Up, InputType::Short), None);
+        assert_eq!(menu.selected(), Some('c'));
+        menu.on_input(InputKey::Down, InputType::Repeat);
+        assert_eq!(menu.cursor(), 0);
+        menu.on_input(InputKey::Down, InputType::Short);
+        menu.on_input(InputKey::Down, InputType::Short);
+        assert_eq!(menu.selected(), Some('c'));
+        // only Short and Repeat move
+        menu.on_input(InputKey::Down, InputType::Press);
+        menu.on_input(InputKey::Down, InputType::Long);
+        assert_eq!(menu.selected(), Some('c'));
+    }
+
+    #[test]
+    fn events() {
+        let mut menu = Menu::new(ITEMS);
+        menu.on_input(InputKey::Down, InputType::Short);
+        let mut event = |key, type_| menu.on_input(key, type_);
+        assert_eq!(
+            event(InputKey::Ok, InputType::Short),
+            Some(MenuEvent::Select('b'))
+        );
+        assert_eq!(event(InputKey::Ok, InputType::Long), None);
+        assert_eq!(
+            event(InputKey::Left, InputType::Short),
+            Some(MenuEvent::Adjust('b', -1))
+        );
+        assert_eq!(
+            event(InputKey::Right, InputType::Repeat),
+            Some(MenuEvent::Adjust('b', 1))
+        );
+        assert_eq!(
+            event(InputKey::Back, InputType::Short),
+            Some(MenuEvent::Close)
+        );
+        assert_eq!(
+            event(InputKey::Back, InputType::Long),
+            Some(MenuEvent::Exit)
+        );
+    }
+
+    #[test]
+    fn empty_menu() {
+        let mut menu = Menu::<char>::new(&[]);
+        assert_eq!(menu.on_input(InputKey::Down, InputType::Short), None);
+        assert_eq!(menu.cursor(), 0);
+        assert_eq!(menu.on_input(InputKey::Ok, InputType::Short), None);
+        assert_eq!(
+            menu.on_input(InputKey::Back, InputType::Short),
+            Some(MenuEvent::Close)
+        );
+    }
+
+    #[test]
+    fn confirm_answers() {
+        assert_eq!(confirm(InputKey::Ok, InputType::Short), Some(true));
+        assert_eq!(confirm(InputKey::Back, InputType::Short), Some(false));
+        assert_eq!(confirm(InputKey::Back, InputType::Press), None);
+        assert_eq!(confirm(InputKey::Up, InputType::Short), None);
+    }
+}
@@ -0,0 +1,444 @@
+use core::{f32::consts::FRAC_1_SQRT_2, iter};
+
+/// How often [`MouseMotion::tick`] is expected to be called, in milliseconds
+pub const TICK_MS: u32 = 1000 / 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// How cursor speed grows while direction is held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccelCurve {
+    /// `base + gain * t`
+    Linear,
+    /// `base + gain * t^2`
+    Quadratic,
+    /// `base * (1 + gain)^t`
+    Exponential,
+}
+
+/// Cursor speed settings, all speeds are in pixels per tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionConfig {
+    pub curve: AccelCurve,
+    /// Speed right after pressing
+    pub base_speed: f32,
+    pub gain: f32,
+    /// Speed cap
+    pub max_speed: f32,
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        Self {
+            curve: AccelCurve::Linear,
+            base_speed: 2.0,
+            gain: 0.5,
+            max_speed: 40.0,
+        }
+    }
+}
+
+impl MotionConfig {
+    /// Speed after direction was held for `ticks` ticks
+    pub fn speed(&self, ticks: u32) -> f32 {
+        let t = ticks as f32;
+        let speed = match self.curve {
+            AccelCurve::Linear => self.base_speed + self.gain * t,
+            AccelCurve::Quadratic => self.base_speed + self.gain * t * t,
+            // doesn't grow, and would be NaN once growth overflows
+            AccelCurve::Exponential if self.base_speed <= 0.0 => 0.0,
+            AccelCurve::Exponential => {
+                self.base_speed * libm::powf(1.0 + self.gain, t)
+            }
+        };
+        speed.clamp(0.0, self.max_speed)
+    }
+}
+
+/// Turns held directions into cursor movement, accelerating over time.
+///
+/// Any number of directions can be held at once, which allows diagonal
+/// movement. Opposite directions cancel out.
+pub struct MouseMotion {
+    config: MotionConfig,
+    held: [bool; 4],
+    /// Ticks since first direction got pressed
+    ticks: u32,
+    /// Sub-pixel movement carried over to next tick
+    remainder: (f32, f32),
+}
+
+impl MouseMotion {
+    pub fn new(config: MotionConfig) -> Self {
+        Self {
+            config,
+            held: [false; 4],
+            ticks: 0,
+            remainder: (0.0, 0.0),
+        }
+    }
+
+    pub fn config(&self) -> &MotionConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: MotionConfig) {
+        self.config = config;
+    }
+
+    /// Starts moving in `direction`.
+    ///
+    /// Acceleration restarts only if nothing else was held.
+    pub fn press(&mut self, direction: Direction) {
+        if !self.is_moving() {
+            self.stop();
+        }
+        self.held[direction as usize] = true;
+    }
+
+    /// Stops moving in `direction`
+    pub fn release(&mut self, direction: Direction) {
+        self.held[direction as usize] = false;
+        if !self.is_moving() {
+            self.stop();
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.held = [false; 4];
+        self.ticks = 0;
+        self.remainder = (0.0, 0.0);
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.held.contains(&true)
+    }
+
+    fn is_held(&self, direction: Direction) -> bool {
+        self.held[direction as usize]
+    }
+
+    /// Advances by single tick, returning cursor delta for it.
+    ///
+    /// Delta can be bigger than fits in single report, see [`split_delta`].
+    pub fn tick(&mut self) -> Option<(i32, i32)> {
+        if !self.is_moving() {
+            return None;
+        }
+        let axis = |neg, pos| {
+            self.is_held(pos) as i8 as f32 - self.is_held(neg) as i8 as f32
+        };
+        let x = axis(Direction::Left, Direction::Right);
+        let y = axis(Direction::Up, Direction::Down);
+        let mut speed = self.config.speed(self.ticks);
+        if x != 0.0 && y != 0.0 {
+            speed *= FRAC_1_SQRT_2;
+        }
+        self.ticks = self.ticks.saturating_add(1);
+
+        let (dx, dy) =
+            (x * speed + self.remainder.0, y * speed + self.remainder.1);
+        let (px, py) = (dx as i32, dy as i32);
+        self.remainder = (dx - px as f32, dy - py as f32);
+        (px != 0 || py != 0).then_some((px, py))
+    }
+}
+
+/// Splits cursor delta into steps that fit in single mouse report
+pub fn split_delta(mut dx: i32, mut dy: i32) -> impl Iterator<Item = (i8, i8)> {
+    let max = i8::MAX as i32;
+    iter::from_fn(move || {
+        if dx == 0 && dy == 0 {
+            return None;
+        }
+        let (step_x, step_y) = (dx.clamp(-max, max), dy.clamp(-max, max));
+        dx -= step_x;
+        dy -= step_y;
+        Some((step_x as i8, step_y as i8))
+    })
+}
+
+/// Turns held Up/Down into scroll wheel steps, accelerating over time
+pub struct ScrollMotion {
+    config: MotionConfig,
+    /// Positive scrolls up
+    held: Option<i8>,
+    ticks: u32,
+    /// Partial wheel step carried over to next tick
+    remainder: f32,
+}
+
+impl ScrollMotion {
+    /// Speeds are in wheel steps per tick
+    pub fn default_config() -> MotionConfig {
+        MotionConfig {
+            curve: AccelCurve::Linear,
+            base_speed: 0.15,
+            gain: 0.01,
+            max_speed: 2.0,
+        }
+    }
+
+    pub fn new(config: MotionConfig) -> Self {
+        Self {
+            config,
+            held: None,
+            ticks: 0,
+            remainder: 0.0,
+        }
+    }
+
+    pub fn config(&self) -> &MotionConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: MotionConfig) {
+        self.config = config;
+    }
+
+    /// Starts scrolling, first tick after it always scrolls by at least one
+    /// step.
+    ///
+    /// Only [`Direction::Up`] and [`Direction::Down`] scroll.
+    pub fn press(&mut self, direction: Direction) {
+        let Some(sign) = Self::sign(direction) else {
+            return;
+        };
+        self.held = Some(sign);
+        self.ticks = 0;
+        self.remainder = 1.0;
+    }
+
+    /// Stops scrolling, if `direction` is the one being held
+    pub fn release(&mut self, direction: Direction) {
+        if self.held.is_some() && self.held == Self::sign(direction) {
+            self.stop();
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.held = None;
+        self.ticks = 0;
+        self.remainder = 0.0;
+    }
+
+    pub fn is_scrolling(&self) -> bool {
+        self.held.is_some()
+    }
+
+    fn sign(direction: Direction) -> Option<i8> {
+        match direction {
+            Direction::Up => Some(1),
+            Direction::Down => Some(-1),
+            Direction::Left | Direction::Right => None,
+        }
+    }
+
+    /// Advances by single tick, returning wheel delta for it
+    pub fn tick(&mut self) -> Option<i32> {
+        let sign = self.held?;
+        let steps = self.config.speed(self.ticks) + self.remainder;
+        self.ticks = self.ticks.saturating_add(1);
+        let whole = steps as i32;
+        self.remainder = steps - whole as f32;
+        (whole != 0).then_some(sign as i32 * whole)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(curve: AccelCurve) -> MotionConfig {
+        MotionConfig {
+            curve,
+            base_speed: 2.0,
+            gain: 0.5,
+            max_speed: 40.0,
+        }
+    }
+
+    #[test]
+    fn linear_speed() {
+        let config = config(AccelCurve::Linear);
+        assert_eq!(config.speed(0), 2.0);
+        assert_eq!(config.speed(1), 2.5);
+        assert_eq!(config.speed(10), 7.0);
+    }
+
+    #[test]
+    fn quadratic_speed() {
+        let config = config(AccelCurve::Quadratic);
+        assert_eq!(config.speed(0), 2.0);
+        assert_eq!(config.speed(2), 4.0);
+        assert_eq!(config.speed(4), 10.0);
+    }
+
+    #[test]
+    fn exponential_speed() {
+        let config = config(AccelCurve::Exponential);
+        assert_eq!(config.speed(0), 2.0);
+        assert_eq!(config.speed(1), 3.0);
+        assert_eq!(config.speed(3), 6.75);
+    }
+
+    #[test]
+    fn speed_is_capped() {
+        for curve in [
+            AccelCurve::Linear,
+            AccelCurve::Quadratic,
+            AccelCurve::Exponential,
+        ] {
+            let config = config(curve);
+            for ticks in [100, 10_000, u32::MAX] {
+                assert_eq!(config.speed(ticks), 40.0, "{curve:?} {ticks}");
+            }
+        }
+    }
+
+    #[test]
+    fn exponential_without_gain_stays_flat() {
+        let exponential = |base_speed, gain| MotionConfig {
+            base_speed,
+            gain,
+            ..config(AccelCurve::Exponential)
+        };
+        assert_eq!(exponential(2.0, 0.0).speed(u32::MAX), 2.0);
+        assert_eq!(exponential(2.0, -0.5).speed(u32::MAX), 0.0);
+        assert_eq!(exponential(0.0, 0.5).speed(u32::MAX), 0.0);
+    }
+
+    #[test]
+    fn speed_never_drops_below_zero() {
+        let config = MotionConfig {
+            gain: -1.0,
+            ..config(AccelCurve::Linear)
+        };
+        assert_eq!(config.speed(100), 0.0);
+    }
+
+    /// Constant speed, no acceleration
+    fn flat(speed: f32) -> MouseMotion {
+        MouseMotion::new(MotionConfig {
+            base_speed: speed,
+            gain: 0.0,
+            ..config(AccelCurve::Linear)
+        })
+    }
+
+    /// Total delta over `ticks` ticks
+    fn travel(motion: &mut MouseMotion, ticks: u32) -> (i32, i32) {
+        (0..ticks)
+            .filter_map(|_| motion.tick())
+            .fold((0, 0), |(x, y), (dx, dy)| (x + dx, y + dy))
+    }
+
+    #[test]
+    fn no_motion_while_nothing_held() {
+        let mut motion = flat(3.0);
+        assert_eq!(motion.tick(), None);
+        motion.press(Direction::Right);
+        motion.release(Direction::Right);
+        assert_eq!(motion.tick(), None);
+    }
+
+    #[test]
+    fn straight_motion() {
+        let mut motion = flat(3.0);
+        motion.press(Direction::Right);
+        assert_eq!(motion.tick(), Some((3, 0)));
+        motion.release(Direction::Right);
+        motion.press(Direction::Up);
+        assert_eq!(motion.tick(), Some((0, -3)));
+    }
+
+    #[test]
+    fn diagonal_motion_keeps_speed() {
+        let mut motion = flat(10.0);
+        motion.press(Direction::Right);
+        motion.press(Direction::Down);
+        assert_eq!(motion.tick(), Some((7, 7)));
+        // 100 ticks at 10 px per tick, split evenly between axes
+        let (x, y) = travel(&mut motion, 99);
+        let expected = 1000.0 * FRAC_1_SQRT_2 - 7.0;
+        assert!((x as f32 - expected).abs() <= 1.0, "{x}");
+        assert_eq!(x, y);
+    }
+
+    #[test]
+    fn opposite_directions_cancel() {
+        let mut motion = flat(3.0);
+        motion.press(Direction::Left);
+        motion.press(Direction::Right);
+        assert!(motion.is_moving());
+        assert_eq!(motion.tick(), None);
+        // not scaled down as diagonal
+        motion.press(Direction::Up);
+        assert_eq!(motion.tick(), Some((0, -3)));
+    }
+
+    #[test]
+    fn sub_pixel_motion_carries_over() {
+        let mut motion = flat(0.4);
+        motion.press(Direction::Left);
+        assert_eq!(motion.tick(), None);
+        assert_eq!(motion.tick(), None);
+        assert_eq!(motion.tick(), Some((-1, 0)));
+        assert_eq!(travel(&mut motion, 7), (-3, 0));
+    }
+
+    #[test]
+    fn stopping_drops_remainder() {
+        let mut motion = flat(0.6);
+        motion.press(Direction::Down);
+        assert_eq!(motion.tick(), None);
+        motion.release(Direction::Down);
+        motion.press(Direction::Down);
+        assert_eq!(motion.tick(), None);
+    }
+
+    #[test]
+    fn acceleration_continues_when_direction_added() {
+        let mut motion = MouseMotion::new(config(AccelCurve::Linear));
+        motion.press(Direction::Up);
+        assert_eq!(motion.tick(), Some((0, -2)));
+        assert_eq!(motion.tick(), Some((0, -2)));
+        motion.press(Direction::Left);
+        // 3.0 px per tick, over both axes, plus 0.5 px carried over on y
+        assert_eq!(motion.tick(), Some((-2, -2)));
+        motion.release(Direction::Left);
+        motion.release(Direction::Up);
+        motion.press(Direction::Up);
+        assert_eq!(motion.tick(), Some((0, -2)));
+    }
+
+    #[test]
+    fn split_small_delta() {
+        assert_eq!(split_delta(5, -3).collect::<Vec<_>>(), [(5, -3)]);
+        assert_eq!(split_delta(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn split_big_delta() {
+        assert_eq!(
+            split_delta(300, -5).collect::<Vec<_>>(),
+            [(127, -5), (127, 0), (46, 0)],
+        );
+        assert_eq!(
+            split_delta(-128, 254).collect::<Vec<_>>(),
+            [(-127, 127), (-1, 127)],
+        );
+    }
+
+    #[test]
+    fn split_delta_never_gives_i8_min() {
+        let steps = split_delta(-100_000, -1000);
+        assert!(steps.flat_map(|(x, y)| [x, y]).all(|v| v >= -127));
+    }
+}
@@ -0,0 +1,465 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    keymap::Keymap,
+    view_port::{InputKey, InputType},
+};
+
+/// Input pattern made of several firmware input events
+#[derive(Debug, Clone, Copy, Eq)]
+pub enum Pattern {
+    DoubleTap(InputKey),
+    TripleTap(InputKey),
+    /// Two inputs pressed together, in either order
+    Chord(InputKey, InputKey),
+    /// Input held for [`PatternConfig::very_long_ms`]
+    VeryLong(InputKey),
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        match (*self, *other) {
+            (Pattern::DoubleTap(a), Pattern::DoubleTap(b))
+            | (Pattern::TripleTap(a), Pattern::TripleTap(b))
+            | (Pattern::VeryLong(a), Pattern::VeryLong(b)) => a == b,
+            (Pattern::Chord(a1, b1), Pattern::Chord(a2, b2)) => {
+                (a1, b1) == (a2, b2) || (a1, b1) == (b2, a2)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// What [`PatternRecognizer`] makes of input events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// Event not part of any pattern, passed through
+    Input(InputKey, InputType),
+    Pattern(Pattern),
+}
+
+/// Timeouts of [`PatternRecognizer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternConfig {
+    /// Longest time between taps of double or triple tap, in ms
+    pub tap_gap_ms: u32,
+    /// Longest time between presses of chord inputs, in ms
+    pub chord_window_ms: u32,
+    /// How long input has to be held to be very long hold, in ms
+    pub very_long_ms: u32,
+}
+
+impl Default for PatternConfig {
+    fn default() -> Self {
+        Self {
+            tap_gap_ms: 300,
+            chord_window_ms: 150,
+            very_long_ms: 2000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Taps {
+    key: InputKey,
+    count: u8,
+    last_ms: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    key: InputKey,
+    pressed_ms: u32,
+    /// Part of chord, rest of its events are swallowed
+    chorded: bool,
+    /// Chorded input was released, Short that follows is swallowed too
+    released: bool,
+    /// Long event was held back
+    long: bool,
+    /// Very long hold was reported
+    very_long: bool,
+}
+
+/// Recognizes [`Pattern`]s bound in keymap among input events, of Basic
+/// mode or, with [`mouse_patterns`](crate::keymap::mouse_patterns), of Mouse
+/// mode.
+///
+/// Only inputs that have patterns bound are affected, and only as much as
+/// needed to tell them apart:
+/// - with double or triple tap bound, Short is held back until no more taps
+///   can follow, delaying single taps by [`PatternConfig::tap_gap_ms`]
+/// - with very long hold bound, Press is swallowed and Long held back until
+///   release, so that it's not sent on way to very long hold
+/// - with chord bound, pressing other input of chord soon enough reports
+///   chord, and swallows rest of both inputs' events
+///
+/// Works on timestamps alone, [`PatternRecognizer::tick`] has to be called
+/// regularly for timeouts to happen.
+pub struct PatternRecognizer {
+    config: PatternConfig,
+    taps: Option<Taps>,
+    pressed: Vec<Press>,
+}
+
+impl PatternRecognizer {
+    pub fn new(config: PatternConfig) -> Self {
+        Self {
+            config,
+            taps: None,
+            pressed: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &PatternConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: PatternConfig) {
+        self.config = config;
+    }
+
+    /// Forgets any half-recognized pattern
+    pub fn reset(&mut self) {
+        self.taps = None;
+        self.pressed.clear();
+    }
+
+    /// Feeds input event that happened at `now_ms`, returning triggers, in
+    /// the order they should happen
+    pub fn on_input(
+        &mut self,
+        keymap: &Keymap,
+        key: InputKey,
+        type_: InputType,
+        now_ms: u32,
+    ) -> Vec<Trigger> {
+        let mut triggers = self.tick(keymap, now_ms);
+        let pending_taps = self.taps.is_some_and(|taps| taps.key == key);
+        let flush = match type_ {
+            InputType::Press => !pending_taps,
+            InputType::Long => true,
+            _ => false,
+        };
+        if flush {
+            triggers.extend(self.flush_taps(keymap));
+        }
+
+        let very_long = has(keymap, Pattern::VeryLong(key));
+        let index = self.pressed.iter().position(|p| p.key == key);
+        if let Some(i) = index.filter(|&i| {
+            let press = &self.pressed[i];
+            press.chorded && !(press.released && type_ == InputType::Press)
+        }) {
+            match type_ {
+                InputType::Release => self.pressed[i].released = true,
+                InputType::Short => {
+                    self.pressed.remove(i);
+                }
+                _ => (),
+            }
+            return triggers;
+        }
+
+        match type_ {
+            InputType::Press => {
+                let chord = self.pressed.iter_mut().find(|p| {
+                    !p.chorded
+                        && now_ms.wrapping_sub(p.pressed_ms)
+                            <= self.config.chord_window_ms
+                        && has(keymap, Pattern::Chord(p.key, key))
+                });
+                let chorded = chord.is_some();
+                if let Some(other) = chord {
+                    other.chorded = true;
+                    triggers
+                        .push(Trigger::Pattern(Pattern::Chord(other.key, key)));
+                }
+                self.pressed.retain(|p| p.key != key);
+                self.pressed.push(Press {
+                    key,
+                    pressed_ms: now_ms,
+                    chorded,
+                    released: false,
+                    long: false,
+                    very_long: false,
+                });
+                if !chorded && !very_long {
+                    triggers.push(Trigger::Input(key, type_));
+                }
+            }
+            InputType::Short if max_taps(keymap, key) > 1 => {
+                let taps = match self.taps {
+                    Some(taps)
+                        if taps.key == key
+                            && now_ms.wrapping_sub(taps.last_ms)
+                                <= self.config.tap_gap_ms =>
+                    {
+                        Taps {
+                            count: taps.count + 1,
+                            last_ms: now_ms,
+                            ..taps
+                        }
+                    }
+                    _ => {
+                        triggers.extend(self.flush_taps(keymap));
+                        Taps {
+                            key,
+                            count: 1,
+                            last_ms: now_ms,
+                        }
+                    }
+                };
+                self.taps = Some(taps);
+                if taps.count >= max_taps(keymap, key) {
+                    triggers.extend(self.flush_taps(keymap));
+                }
+            }
+            InputType::Long | InputType::Repeat if very_long => {
+                if let Some(press) = index.map(|i| &mut self.pressed[i]) {
+                    press.long |= type_ == InputType::Long;
+                }
+            }
+            InputType::Release => {
+                let press = index.map(|i| self.pressed.remove(i));
+                if !very_long {
+                    triggers.push(Trigger::Input(key, type_));
+                } else if press.is_some_and(|p| p.long && !p.very_long) {
+                    triggers.push(Trigger::Input(key, InputType::Long));
+                }
+            }
+            _ => triggers.push(Trigger::Input(key, type_)),
+        }
+        triggers
+    }
+
+    /// Reports patterns that timed out by `now_ms`
+    pub fn tick(&mut self, keymap: &Keymap, now_ms: u32) -> Vec<Trigger> {
+        let mut triggers = Vec::new();
+        if self.taps.is_some_and(|taps| {
+            now_ms.wrapping_sub(taps.last_ms) > self.config.tap_gap_ms
+        }) {
+            triggers.extend(self.flush_taps(keymap));
+        }
+        for press in &mut self.pressed {
+            let held_ms = now_ms.wrapping_sub(press.pressed_ms);
+            if !press.chorded
+                && !press.very_long
+                && held_ms >= self.config.very_long_ms
+                && has(keymap, Pattern::VeryLong(press.key))
+            {
+                press.very_long = true;
+                triggers.push(Trigger::Pattern(Pattern::VeryLong(press.key)));
+            }
+        }
+        triggers
+    }
+
+    /// Reports pending taps as whatever they add up to
+    fn flush_taps(&mut self, keymap: &Keymap) -> Vec<Trigger> {
+        let Some(Taps { key, count, .. }) = self.taps.take() else {
+            return Vec::new();
+        };
+        let short = Trigger::Input(key, InputType::Short);
+        match count {
+            1 => vec![short],
+            2 if has(keymap, Pattern::DoubleTap(key)) => {
+                vec![Trigger::Pattern(Pattern::DoubleTap(key))]
+            }
+            3 => vec![Trigger::Pattern(Pattern::TripleTap(key))],
+            _ => vec![short; count as usize],
+        }
+    }
+}
+
+fn has(keymap: &Keymap, pattern: Pattern) -> bool {
+    keymap.get_pattern(pattern).is_some()
+}
+
+/// Most taps of `key` that can make a pattern
+fn max_taps(keymap: &Keymap, key: InputKey) -> u8 {
+    if has(keymap, Pattern::TripleTap(key)) {
+        3
+    } else if has(keymap, Pattern::DoubleTap(key)) {
+        2
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use InputKey::{Down, Ok, Up};
+    use InputType::{Long, Press, Release, Short};
+
+    /// Feeds `events`, ticking every 10 ms until `end_ms`, giving triggers
+    /// with time they happened at
+    fn run(
+        keymap: &str,
+        events: &[(u32, InputKey, InputType)],
+        end_ms: u32,
+    ) -> Vec<(u32, Trigger)> {
+        let keymap = Keymap::parse(keymap).unwrap();
+        let mut recognizer = PatternRecognizer::new(PatternConfig::default());
+        let mut triggers = Vec::new();
+        let mut events = events.iter().peekable();
+        for now in (0..=end_ms).step_by(10) {
+            let mut new = recognizer.tick(&keymap, now);
+            while let Some(&(_, key, type_)) = events.next_if(|e| e.0 <= now) {
+                new.extend(recognizer.on_input(&keymap, key, type_, now));
+            }
+            triggers.extend(new.into_iter().map(|trigger| (now, trigger)));
+        }
+        triggers
+    }
+
+    fn tap(at_ms: u32, key: InputKey) -> [(u32, InputKey, InputType); 3] {
+        [
+            (at_ms, key, Press),
+            (at_ms + 50, key, Release),
+            (at_ms + 50, key, Short),
+        ]
+    }
+
+    fn input(at_ms: u32, key: InputKey, type_: InputType) -> (u32, Trigger) {
+        (at_ms, Trigger::Input(key, type_))
+    }
+
+    fn pattern(at_ms: u32, pattern: Pattern) -> (u32, Trigger) {
+        (at_ms, Trigger::Pattern(pattern))
+    }
+
+    #[test]
+    fn unbound_inputs_pass_through() {
+        let triggers = run("", &tap(0, Ok), 500);
+        assert_eq!(
+            triggers,
+            [
+                input(0, Ok, Press),
+                input(50, Ok, Release),
+                input(50, Ok, Short)
+            ]
+        );
+    }
+
+    #[test]
+    fn single_tap_waits_for_tap_gap() {
+        let triggers = run("Ok Double = F", &tap(0, Ok), 500);
+        // flushed once more than 300 ms passed since it
+        assert_eq!(
+            triggers,
+            [
+                input(0, Ok, Press),
+                input(50, Ok, Release),
+                input(360, Ok, Short)
+            ]
+        );
+    }
+
+    #[test]
+    fn double_tap_is_reported_on_second_tap() {
+        let events = [tap(0, Ok), tap(200, Ok)].concat();
+        let triggers = run("Ok Double = F", &events, 1000);
+        assert_eq!(
+            triggers.last(),
+            Some(&pattern(250, Pattern::DoubleTap(Ok)))
+        );
+        assert!(!triggers.contains(&input(250, Ok, Short)));
+    }
+
+    #[test]
+    fn double_tap_flushes_on_timeout_when_triple_is_bound() {
+        let events = [tap(0, Ok), tap(200, Ok)].concat();
+        let keymap = "Ok Double = F\nOk Triple = M";
+        let triggers = run(keymap, &events, 1000);
+        assert_eq!(
+            triggers.last(),
+            Some(&pattern(560, Pattern::DoubleTap(Ok)))
+        );
+    }
+
+    #[test]
+    fn triple_tap() {
+        let events = [tap(0, Ok), tap(200, Ok), tap(400, Ok)].concat();
+        let keymap = "Ok Double = F\nOk Triple = M";
+        let patterns: Vec<_> = run(keymap, &events, 1000)
+            .into_iter()
+            .filter(|(_, trigger)| matches!(trigger, Trigger::Pattern(_)))
+            .collect();
+        assert_eq!(patterns, [pattern(450, Pattern::TripleTap(Ok))]);
+    }
+
+    #[test]
+    fn slow_taps_stay_single() {
+        let events = [tap(0, Ok), tap(500, Ok)].concat();
+        let shorts: Vec<_> = run("Ok Double = F", &events, 1000)
+            .into_iter()
+            .filter(|(_, trigger)| *trigger == Trigger::Input(Ok, Short))
+            .collect();
+        assert_eq!(shorts, [input(360, Ok, Short), input(860, Ok, Short)]);
+    }
+
+    #[test]
+    fn chord_in_either_order() {
+        for (first, second) in [(Up, Down), (Down, Up)] {
+            let events = [
+                (0, first, Press),
+                (100, second, Press),
+                (300, first, Release),
+                (300, first, Short),
+                (320, second, Release),
+                (320, second, Short),
+            ];
+            let triggers = run("Up+Down = C", &events, 500);
+            assert_eq!(
+                triggers,
+                [
+                    input(0, first, Press),
+                    pattern(100, Pattern::Chord(Up, Down)),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn input_works_again_after_held_chord() {
+        let events = [
+            (0, Up, Press),
+            (50, Down, Press),
+            (500, Up, Long),
+            (800, Up, Release),
+            (800, Down, Release),
+            (1000, Up, Press),
+        ];
+        let triggers = run("Up+Down = C", &events, 1100);
+        assert_eq!(triggers.last(), Some(&input(1000, Up, Press)));
+    }
+
+    #[test]
+    fn chord_needs_presses_within_window() {
+        let events = [(0, Up, Press), (160, Down, Press)];
+        let triggers = run("Up+Down = C", &events, 500);
+        assert_eq!(triggers, [input(0, Up, Press), input(160, Down, Press)]);
+    }
+
+    #[test]
+    fn very_long_holds_back_long() {
+        let keymap = "Ok VeryLong = K";
+        let long = [(0, Ok, Press), (500, Ok, Long), (800, Ok, Release)];
+        assert_eq!(run(keymap, &long, 3000), [input(800, Ok, Long)]);
+        let very_long = [(0, Ok, Press), (500, Ok, Long), (2500, Ok, Release)];
+        assert_eq!(
+            run(keymap, &very_long, 3000),
+            [pattern(2000, Pattern::VeryLong(Ok))]
+        );
+    }
+
+    #[test]
+    fn reset_forgets_pending_taps() {
+        let keymap = Keymap::parse("Ok Double = F").unwrap();
+        let mut recognizer = PatternRecognizer::new(PatternConfig::default());
+        recognizer.on_input(&keymap, Ok, Short, 0);
+        recognizer.reset();
+        assert_eq!(recognizer.tick(&keymap, 1000), []);
+    }
+}
@@ -0,0 +1,356 @@
+use core::ffi::CStr;
+
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    bt::{ConsumerKey, Key, KeyMods},
+    keymap::{Action, Binding, Keymap},
+    view_port::{InputKey, InputType},
+};
+
+/// Named set of Basic mode bindings for a specific player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: &'static CStr,
+    pub keymap: Keymap,
+}
+
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    pub fn builtin() -> Self {
+        Self {
+            profiles: vec![
+                Profile {
+                    name: c"YouTube",
+                    keymap: Keymap::builtin(),
+                },
+                Profile {
+                    name: c"Netflix",
+                    keymap: netflix(),
+                },
+                Profile {
+                    name: c"VLC",
+                    keymap: vlc(),
+                },
+                Profile {
+                    name: c"Spotify",
+                    keymap: spotify(),
+                },
+                Profile {
+                    name: c"Twitch",
+                    keymap: twitch(),
+                },
+            ],
+        }
+    }
+
+    /// Adds profile, replacing one with the same name
+    pub fn add(&mut self, profile: Profile) {
+        match self.position(profile.name) {
+            Some(index) => self.profiles[index] = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Profile> {
+        self.profiles.get(index)
+    }
+
+    pub fn position(&self, name: &CStr) -> Option<usize> {
+        self.profiles.iter().position(|p| p.name == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Index `delta` profiles away from `index`, wrapping around
+    pub fn cycle(&self, index: usize, delta: isize) -> usize {
+        let len = self.len() as isize;
+        (index as isize + delta).rem_euclid(len.max(1)) as usize
+    }
+}
+
+/// Builds keymap from `table`, with Up/Down Long changing system volume.
+/// Holding Up/Down keeps changing volume, and holding Left/Right keeps
+/// seeking with their Short binding.
+fn with_volume(table: &[(InputKey, InputType, Action)]) -> Keymap {
+    let mut keymap = Keymap::from_table(table);
+    let volume = [
+        (InputKey::Up, ConsumerKey::VolumeIncrease),
+        (InputKey::Down, ConsumerKey::VolumeDecrease),
+    ];
+    for (key, consumer_key) in volume {
+        for type_ in [InputType::Long, InputType::Repeat] {
+            keymap.bind(Binding {
+                key,
+                type_,
+                action: Action::ConsumerTap(consumer_key),
+            });
+        }
+    }
+    for key in [InputKey::Left, InputKey::Right] {
+        if let Some(action) = keymap.get(key, InputType::Short) {
+            keymap.bind(Binding {
+                key,
+                type_: InputType::Repeat,
+                action,
+            });
+        }
+    }
+    keymap
+}
+
+/// Arrows seek by 10s, no speed or frame controls, so those slots get mute
+/// and skip intro
+fn netflix() -> Keymap {
+    with_volume(&[
+        (
+            InputKey::Ok,
+            InputType::Short,
+            Action::KeyTap(Key::Spacebar.into()),
+        ),
+        (InputKey::Ok, InputType::Long, Action::KeyTap(Key::F.into())),
+        (
+            InputKey::Left,
+            InputType::Short,
+            Action::KeyTap(Key::LeftArrow.into()),
+        ),
+        (
+            InputKey::Right,
+            InputType::Short,
+            Action::KeyTap(Key::RightArrow.into()),
+        ),
+        (
+            InputKey::Left,
+            InputType::Long,
+            Action::KeyTap(Key::M.into()),
+        ),
+        (
+            InputKey::Right,
+            InputType::Long,
+            Action::KeyTap(Key::S.into()),
+        ),
+        (
+            InputKey::Up,
+            InputType::Short,
+            Action::KeyTap(Key::UpArrow.into()),
+        ),
+        (
+            InputKey::Down,
+            InputType::Short,
+            Action::KeyTap(Key::DownArrow.into()),
+        ),
+    ])
+}
+
+/// Alt+arrows seek by 10s, brackets change speed, `E` steps single frame
+/// forward, and Shift+Left jumps 3s back in place of previous frame
+fn vlc() -> Keymap {
+    let (shift, alt) = (KeyMods::LeftShift, KeyMods::LeftAlt);
+    with_volume(&[
+        (
+            InputKey::Ok,
+            InputType::Short,
+            Action::KeyTap(Key::Spacebar.into()),
+        ),
+        (InputKey::Ok, InputType::Long, Action::KeyTap(Key::F.into())),
+        (
+            InputKey::Left,
+            InputType::Short,
+            Action::KeyTap(Key::LeftArrow | alt),
+        ),
+        (
+            InputKey::Right,
+            InputType::Short,
+            Action::KeyTap(Key::RightArrow | alt),
+        ),
+        (
+            InputKey::Left,
+            InputType::Long,
+            Action::KeyTap(Key::LeftBracket.into()),
+        ),
+        (
+            InputKey::Right,
+            InputType::Long,
+            Action::KeyTap(Key::RightBracket.into()),
+        ),
+        (
+            InputKey::Up,
+            InputType::Short,
+            Action::KeyTap(Key::E.into()),
+        ),
+        (
+            InputKey::Down,
+            InputType::Short,
+            Action::KeyTap(Key::LeftArrow | shift),
+        ),
+    ])
+}
+
+/// Shift+arrows seek, Ctrl+arrows switch tracks and change app volume
+fn spotify() -> Keymap {
+    let (shift, ctrl) = (KeyMods::LeftShift, KeyMods::LeftCtrl);
+    with_volume(&[
+        (
+            InputKey::Ok,
+            InputType::Short,
+            Action::KeyTap(Key::Spacebar.into()),
+        ),
+        (InputKey::Ok, InputType::Long, Action::KeyTap(Key::S | ctrl)),
+        (
+            InputKey::Left,
+            InputType::Short,
+            Action::KeyTap(Key::LeftArrow | shift),
+        ),
+        (
+            InputKey::Right,
+            InputType::Short,
+            Action::KeyTap(Key::RightArrow | shift),
+        ),
+        (
+            InputKey::Left,
+            InputType::Long,
+            Action::KeyTap(Key::LeftArrow | ctrl),
+        ),
+        (
+            InputKey::Right,
+            InputType::Long,
+            Action::KeyTap(Key::RightArrow | ctrl),
+        ),
+        (
+            InputKey::Up,
+            InputType::Short,
+            Action::KeyTap(Key::UpArrow | ctrl),
+        ),
+        (
+            InputKey::Down,
+            InputType::Short,
+            Action::KeyTap(Key::DownArrow | ctrl),
+        ),
+    ])
+}
+
+/// Arrows seek in VODs and change player volume, `M` mutes and Alt+T toggles
+/// theatre mode
+fn twitch() -> Keymap {
+    let alt = KeyMods::LeftAlt;
+    with_volume(&[
+        (
+            InputKey::Ok,
+            InputType::Short,
+            Action::KeyTap(Key::Spacebar.into()),
+        ),
+        (InputKey::Ok, InputType::Long, Action::KeyTap(Key::F.into())),
+        (
+            InputKey::Left,
+            InputType::Short,
+            Action::KeyTap(Key::LeftArrow.into()),
+        ),
+        (
+            InputKey::Right,
+            InputType::Short,
+            Action::KeyTap(Key::RightArrow.into()),
+        ),
+        (
+            InputKey::Left,
+            InputType::Long,
+            Action::KeyTap(Key::M.into()),
+        ),
+        (
+            InputKey::Right,
+            InputType::Long,
+            Action::KeyTap(Key::T | alt),
+        ),
+        (
+            InputKey::Up,
+            InputType::Short,
+            Action::KeyTap(Key::UpArrow.into()),
+        ),
+        (
+            InputKey::Down,
+            InputType::Short,
+            Action::KeyTap(Key::DownArrow.into()),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_replaces_same_name() {
+        let mut profiles = Profiles::builtin();
+        let len = profiles.len();
+        profiles.add(Profile {
+            name: c"Custom",
+            keymap: Keymap::builtin(),
+        });
+        assert_eq!(profiles.len(), len + 1);
+        assert_eq!(profiles.position(c"Custom"), Some(len));
+        profiles.add(Profile {
+            name: c"Netflix",
+            keymap: vlc(),
+        });
+        assert_eq!(profiles.len(), len + 1);
+        let netflix = profiles.get(profiles.position(c"Netflix").unwrap());
+        assert_eq!(netflix.map(|p| &p.keymap), Some(&vlc()));
+    }
+
+    #[test]
+    fn cycle_wraps_both_ways() {
+        let profiles = Profiles::builtin();
+        let last = profiles.len() - 1;
+        assert_eq!(profiles.cycle(0, 1), 1);
+        assert_eq!(profiles.cycle(last, 1), 0);
+        assert_eq!(profiles.cycle(0, -1), last);
+        assert_eq!(profiles.cycle(1, -3), last - 1);
+        assert_eq!(profiles.cycle(2, profiles.len() as isize), 2);
+    }
+
+    #[test]
+    fn cycle_on_empty_list() {
+        let profiles = Profiles {
+            profiles: Vec::new(),
+        };
+        assert!(profiles.is_empty());
+        assert_eq!(profiles.cycle(0, 1), 0);
+        assert_eq!(profiles.cycle(0, -1), 0);
+    }
+
+    #[test]
+    fn builtin_profiles_repeat_arrows() {
+        let profiles = Profiles::builtin();
+        let arrows = [
+            InputKey::Up,
+            InputKey::Down,
+            InputKey::Left,
+            InputKey::Right,
+        ];
+        for profile in (0..profiles.len()).filter_map(|i| profiles.get(i)) {
+            for key in arrows {
+                assert!(
+                    profile.keymap.get(key, InputType::Repeat).is_some(),
+                    "{:?} {key:?}",
+                    profile.name
+                );
+            }
+            assert_eq!(
+                profile.keymap.get(InputKey::Up, InputType::Repeat),
+                Some(Action::ConsumerTap(ConsumerKey::VolumeIncrease))
+            );
+            assert_eq!(
+                profile.keymap.get(InputKey::Left, InputType::Repeat),
+                profile.keymap.get(InputKey::Left, InputType::Short)
+            );
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use alloc::vec::Vec;
+
+use crate::{
+    keymap::{Action, Keymap, Mode},
+    view_port::{InputKey, InputType},
+};
+
+/// Timing of [`HoldRepeat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepeatConfig {
+    /// How long input has to be held before it starts repeating, in ms
+    pub delay_ms: u32,
+    /// Time between steps, in ms
+    pub interval_ms: u32,
+    /// How long input has to repeat before steps speed up, in ms, `None`
+    /// to keep them steady
+    pub accel_after_ms: Option<u32>,
+    /// Time between sped up steps, in ms
+    pub fast_interval_ms: u32,
+}
+
+impl Default for RepeatConfig {
+    fn default() -> Self {
+        Self {
+            delay_ms: 800,
+            interval_ms: 400,
+            accel_after_ms: Some(2000),
+            fast_interval_ms: 250,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// Held for less than Long
+    Pressed,
+    /// Held for Long, but not repeating yet
+    Long,
+    /// Repeating since `since_ms`, with next step due at `next_ms`
+    Repeating { since_ms: u32, next_ms: u32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Hold {
+    key: InputKey,
+    pressed_ms: u32,
+    phase: Phase,
+}
+
+/// Turns holding Basic mode input that has `Repeat` binding into steady
+/// stream of steps.
+///
+/// Such input goes through these states:
+/// - pressed: released here, it sends its Short binding, as usual
+/// - long: released here, it sends its Long binding. As Long is only known
+///   to not turn into repeating on release, it's sent then, instead of
+///   right away.
+/// - repeating: after [`RepeatConfig::delay_ms`], it sends its `Repeat`
+///   binding every [`RepeatConfig::interval_ms`], and once repeating for
+///   [`RepeatConfig::accel_after_ms`], every
+///   [`RepeatConfig::fast_interval_ms`], switching to
+///   [`Keymap::fast_step`] if there is one. Release sends nothing.
+///
+/// Inputs without `Repeat` binding are mapped by [`Keymap::map`] as is,
+/// and firmware's own Repeat events are ignored, as steps are timed by
+/// [`HoldRepeat::tick`].
+pub struct HoldRepeat {
+    config: RepeatConfig,
+    hold: Option<Hold>,
+}
+
+impl HoldRepeat {
+    pub fn new(config: RepeatConfig) -> Self {
+        Self { config, hold: None }
+    }
+
+    pub fn config(&self) -> &RepeatConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: RepeatConfig) {
+        self.config = config;
+    }
+
+    /// Forgets held input, stopping any repeating
+    pub fn reset(&mut self) {
+        self.hold = None;
+    }
+
+    pub fn is_repeating(&self) -> bool {
+        matches!(
+            self.hold,
+            Some(Hold {
+                phase: Phase::Repeating { .. },
+                ..
+            })
+        )
+    }
+
+    /// Maps Basic mode input event that happened at `now_ms` to actions, in
+    /// the order they should happen
+    pub fn on_input(
+        &mut self,
+        keymap: &Keymap,
+        key: InputKey,
+        type_: InputType,
+        now_ms: u32,
+    ) -> Vec<Action> {
+        if keymap.get(key, InputType::Repeat).is_none() {
+            return keymap.map(key, type_, Mode::Basic);
+        }
+        let held = self.hold.as_mut().filter(|hold| hold.key == key);
+        match (type_, held) {
+            (InputType::Press, _) => {
+                self.hold = Some(Hold {
+                    key,
+                    pressed_ms: now_ms,
+                    phase: Phase::Pressed,
+                });
+                Vec::new()
+            }
+            (InputType::Short | InputType::Long, Some(hold))
+                if matches!(hold.phase, Phase::Repeating { .. }) =>
+            {
+                Vec::new()
+            }
+            (InputType::Long, Some(hold)) => {
+                hold.phase = Phase::Long;
+                Vec::new()
+            }
+            (InputType::Release, Some(hold)) => {
+                let phase = hold.phase;
+                self.hold = None;
+                match phase {
+                    Phase::Long => {
+                        keymap.map(key, InputType::Long, Mode::Basic)
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            // another input was pressed since, so hold of this one is no
+            // longer tracked
+            (InputType::Short | InputType::Long, _) => {
+                keymap.map(key, type_, Mode::Basic)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Gives step that is due at `now_ms`, if held input is repeating.
+    ///
+    /// Should be called often, at least as often as
+    /// [`RepeatConfig::fast_interval_ms`].
+    pub fn tick(&mut self, keymap: &Keymap, now_ms: u32) -> Option<Action> {
+        let config = self.config;
+        let hold = self.hold.as_mut()?;
+        let step = keymap.get(hold.key, InputType::Repeat)?;
+        match hold.phase {
+            Phase::Repeating { since_ms, next_ms } => {
+                // wrapping "now_ms < next_ms"
+                if (now_ms.wrapping_sub(next_ms) as i32) < 0 {
+                    return None;
+                }
+                let fast = config.accel_after_ms.is_some_and(|after_ms| {
+                    now_ms.wrapping_sub(since_ms) >= after_ms
+                });
+                let interval_ms = if fast {
+                    config.fast_interval_ms
+                } else {
+                    config.interval_ms
+                };
+                hold.phase = Phase::Repeating {
+                    since_ms,
+                    next_ms: now_ms.wrapping_add(interval_ms),
+                };
+                if fast {
+                    Some(keymap.fast_step(hold.key).unwrap_or(step))
+                } else {
+                    Some(step)
+                }
+            }
+            _ if now_ms.wrapping_sub(hold.pressed_ms) >= config.delay_ms => {
+                hold.phase = Phase::Repeating {
+                    since_ms: now_ms,
+                    next_ms: now_ms.wrapping_add(config.interval_ms),
+                };
+                Some(step)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bt::{Key, KeyMods};
+    use InputKey::{Left, Right};
+    use InputType::{Long, Press, Release, Repeat, Short};
+
+    fn tap(key: Key) -> Action {
+        Action::KeyTap(key.into())
+    }
+
+    /// Feeds `events`, at times relative to `start_ms`, ticking every 10 ms
+    /// until `end_ms`, giving actions with relative time they happened at
+    fn run(
+        config: RepeatConfig,
+        start_ms: u32,
+        events: &[(u32, InputKey, InputType)],
+        end_ms: u32,
+    ) -> Vec<(u32, Action)> {
+        let keymap = Keymap::builtin();
+        let mut repeat = HoldRepeat::new(config);
+        let mut actions = Vec::new();
+        let mut events = events.iter().peekable();
+        for t in (0..=end_ms).step_by(10) {
+            let now = start_ms.wrapping_add(t);
+            while let Some(&(_, key, type_)) = events.next_if(|e| e.0 <= t) {
+                let mapped = repeat.on_input(&keymap, key, type_, now);
+                actions.extend(mapped.into_iter().map(|action| (t, action)));
+            }
+            actions.extend(repeat.tick(&keymap, now).map(|action| (t, action)));
+        }
+        actions
+    }
+
+    /// Held for 3.5 s, with firmware's Long and Repeat events along the way
+    const HOLD: &[(u32, InputKey, InputType)] = &[
+        (0, Left, Press),
+        (500, Left, Long),
+        (650, Left, Repeat),
+        (800, Left, Repeat),
+        (3500, Left, Release),
+    ];
+
+    fn held_steps() -> Vec<(u32, Action)> {
+        let step = tap(Key::LeftArrow);
+        let fast = tap(Key::J);
+        vec![
+            (800, step),
+            (1200, step),
+            (1600, step),
+            (2000, step),
+            (2400, step),
+            // repeating for 2 s
+            (2800, fast),
+            (3050, fast),
+            (3300, fast),
+        ]
+    }
+
+    #[test]
+    fn tap_sends_short() {
+        let events =
+            [(0, Left, Press), (100, Left, Release), (100, Left, Short)];
+        let actions = run(RepeatConfig::default(), 0, &events, 1000);
+        assert_eq!(actions, [(100, tap(Key::LeftArrow))]);
+    }
+
+    #[test]
+    fn long_is_sent_on_release() {
+        let events =
+            [(0, Left, Press), (500, Left, Long), (700, Left, Release)];
+        let actions = run(RepeatConfig::default(), 0, &events, 1000);
+        let long = Action::KeyTap(Key::Comma | KeyMods::LeftShift);
+        assert_eq!(actions, [(700, long)]);
+    }
+
+    #[test]
+    fn hold_repeats_then_speeds_up() {
+        let mut repeat = HoldRepeat::new(RepeatConfig::default());
+        assert!(!repeat.is_repeating());
+        let actions = run(RepeatConfig::default(), 0, HOLD, 4000);
+        assert_eq!(actions, held_steps());
+        let keymap = Keymap::builtin();
+        repeat.on_input(&keymap, Left, Press, 0);
+        assert_eq!(repeat.tick(&keymap, 800), Some(tap(Key::LeftArrow)));
+        assert!(repeat.is_repeating());
+        assert_eq!(repeat.on_input(&keymap, Left, Release, 900), []);
+        assert_eq!(repeat.tick(&keymap, 1200), None);
+    }
+
+    #[test]
+    fn steady_without_accel() {
+        let config = RepeatConfig {
+            accel_after_ms: None,
+            ..RepeatConfig::default()
+        };
+        let actions = run(config, 0, HOLD, 4000);
+        assert!(actions.iter().all(|&(_, a)| a == tap(Key::LeftArrow)));
+        assert_eq!(actions.len(), 7);
+    }
+
+    #[test]
+    fn short_and_long_are_dropped_while_repeating() {
+        let config = RepeatConfig {
+            delay_ms: 300,
+            ..RepeatConfig::default()
+        };
+        let events = [(0, Left, Press), (500, Left, Long), (600, Left, Short)];
+        let actions = run(config, 0, &events, 600);
+        let step = tap(Key::LeftArrow);
+        assert_eq!(actions, [(300, step)]);
+    }
+
+    #[test]
+    fn inputs_without_repeat_pass_through() {
+        let keymap = Keymap::builtin();
+        let mut repeat = HoldRepeat::new(RepeatConfig::default());
+        for type_ in [Press, Short, Long, Release] {
+            assert_eq!(
+                repeat.on_input(&keymap, InputKey::Ok, type_, 0),
+                keymap.map(InputKey::Ok, type_, Mode::Basic),
+            );
+        }
+        assert_eq!(
+            repeat.on_input(&keymap, InputKey::Ok, Short, 0),
+            [tap(Key::Spacebar)]
+        );
+        assert_eq!(repeat.tick(&keymap, 5000), None);
+    }
+
+    #[test]
+    fn other_input_takes_over_hold() {
+        let events = [
+            (0, Left, Press),
+            (300, Right, Press),
+            (400, Left, Release),
+            (400, Left, Short),
+            (500, Right, Release),
+            (500, Right, Short),
+        ];
+        let actions = run(RepeatConfig::default(), 0, &events, 1500);
+        assert_eq!(
+            actions,
+            [(400, tap(Key::LeftArrow)), (500, tap(Key::RightArrow))]
+        );
+    }
+
+    #[test]
+    fn time_wraps_around() {
+        let start_ms = u32::MAX - 1000;
+        let actions = run(RepeatConfig::default(), start_ms, HOLD, 4000);
+        assert_eq!(actions, held_steps());
+    }
+}
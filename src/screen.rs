@@ -0,0 +1,462 @@
+//! Screens app can show, and stack of them it navigates
+
+use core::ffi::CStr;
+
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(target_os = "none")]
+use flipperzero::furi::message_queue::MessageQueue;
+#[cfg(target_os = "none")]
+use flipperzero_sys as sys;
+
+use crate::{
+    hid::HidSink,
+    keymap::{Action, Keymap},
+    macros::{Macros, Recorder},
+    profile::Profiles,
+    search::History,
+    settings::Settings,
+    view_port::{CanvasView, InputEvent},
+};
+#[cfg(target_os = "none")]
+use crate::{imu::icm42688::Icm42688, storage::Storage};
+#[cfg(not(target_os = "none"))]
+use fake::{Icm42688, MessageQueue, sys};
+
+/// Stands in for firmware and sensor on host, so navigation can be tested
+#[cfg(not(target_os = "none"))]
+mod fake {
+    use core::marker::PhantomData;
+
+    /// Never read on host
+    pub struct MessageQueue<T>(PhantomData<T>);
+
+    impl<T> Default for MessageQueue<T> {
+        fn default() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    /// No sensor on host
+    pub enum Icm42688 {}
+
+    pub mod sys {
+        /// # Safety
+        /// Always safe, matches signature of real one
+        pub unsafe fn furi_get_tick() -> u32 {
+            0
+        }
+    }
+}
+
+/// Name screen is registered under
+pub type ScreenId = &'static str;
+
+/// Where to go after screen handled input or tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Stay,
+    /// Open screen on top of current one
+    Push(ScreenId),
+    /// Go back to previous screen
+    Pop,
+    /// Swap current screen for another one
+    Replace(ScreenId),
+    Exit,
+}
+
+impl Transition {
+    /// `self`, unless it's [`Transition::Stay`]
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            Transition::Stay => other,
+            _ => self,
+        }
+    }
+}
+
+/// State shared by all screens
+pub struct App<'a> {
+    /// App's input events, for screens that read them while busy, like
+    /// when typing
+    pub events: &'a MessageQueue<InputEvent>,
+    pub profiles: Profiles,
+    /// Index of active profile
+    pub profile: usize,
+    /// No sensor attached means no air mouse mode and no gestures
+    pub imu: Option<Icm42688>,
+    pub gestures_enabled: bool,
+    pub settings: Settings,
+    pub history: History,
+    pub macros: Macros,
+    /// Macro being recorded, if there is one
+    pub recorder: Option<Recorder>,
+    /// Host slot to switch to, or to restart with changed identity, set by
+    /// screens and handled by main loop, as it owns HID profile that has to
+    /// be restarted. If profile can't be started, main loop goes back to
+    /// previous host, setting [`Settings::host`] back.
+    pub switch_host: Option<usize>,
+}
+
+impl App<'_> {
+    pub fn keymap(&self) -> &Keymap {
+        &self.profiles.get(self.profile).unwrap().keymap
+    }
+
+    pub fn profile_name(&self) -> &'static CStr {
+        self.profiles.get(self.profile).map_or(c"", |p| p.name)
+    }
+
+    /// Makes profile at `index` active, saving it as the one to start with
+    /// next time
+    pub fn set_profile(&mut self, index: usize) {
+        self.profile = index;
+        let name = self.profiles.get(index).map(|p| p.name.to_str());
+        let name = match name {
+            Some(Ok(name)) => Some(name.into()),
+            _ => None,
+        };
+        if name != self.settings.profile {
+            self.settings.profile = name;
+            #[cfg(target_os = "none")]
+            let _ = self.settings.save(&Storage::open());
+        }
+    }
+
+    /// Adds action to macro being recorded, if there is one
+    pub fn record(&mut self, action: Action) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(action, unsafe { sys::furi_get_tick() });
+        }
+    }
+}
+
+pub trait Screen {
+    fn draw(&self, app: &App, canvas: &mut CanvasView<'_>);
+
+    fn on_input(
+        &mut self,
+        app: &mut App,
+        event: &InputEvent,
+        hid: &dyn HidSink,
+    ) -> Transition;
+
+    /// Called once every loop iteration, whether there was input or not
+    fn on_tick(&mut self, _app: &mut App, _hid: &dyn HidSink) -> Transition {
+        Transition::Stay
+    }
+
+    /// Called when screen becomes top one, either opened or uncovered
+    fn on_enter(&mut self, _app: &mut App, _hid: &dyn HidSink) {}
+
+    /// Called when screen stops being top one, either closed or covered,
+    /// should let go of anything still held on host
+    fn on_leave(&mut self, _app: &mut App, _hid: &dyn HidSink) {}
+}
+
+/// Registry of screens, and stack of open ones, top one getting drawn and
+/// receiving input
+pub struct Navigator<'a> {
+    app: App<'a>,
+    screens: Vec<(ScreenId, Box<dyn Screen + Send>)>,
+    stack: Vec<usize>,
+}
+
+impl<'a> Navigator<'a> {
+    pub fn new(app: App<'a>) -> Self {
+        Self {
+            app,
+            screens: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn app(&self) -> &App<'a> {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut App<'a> {
+        &mut self.app
+    }
+
+    /// Adds screen, replacing previous one with the same id
+    pub fn register(
+        &mut self,
+        id: ScreenId,
+        screen: impl Screen + Send + 'static,
+    ) {
+        match self.find(id) {
+            Some(index) => self.screens[index].1 = Box::new(screen),
+            None => self.screens.push((id, Box::new(screen))),
+        }
+    }
+
+    /// Id of top screen
+    pub fn current(&self) -> Option<ScreenId> {
+        self.stack.last().map(|&index| self.screens[index].0)
+    }
+
+    /// Opens first screen, returning `false` if there is no such screen
+    pub fn start(&mut self, id: ScreenId, hid: &dyn HidSink) -> bool {
+        self.find(id).is_some() && self.apply(Transition::Push(id), hid)
+    }
+
+    pub fn draw(&self, canvas: &mut CanvasView<'_>) {
+        if let Some(&index) = self.stack.last() {
+            self.screens[index].1.draw(&self.app, canvas);
+        }
+    }
+
+    /// Passes input to top screen, returning `false` once app should exit
+    pub fn on_input(&mut self, event: &InputEvent, hid: &dyn HidSink) -> bool {
+        let Some(&index) = self.stack.last() else {
+            return false;
+        };
+        let transition =
+            self.screens[index].1.on_input(&mut self.app, event, hid);
+        self.apply(transition, hid)
+    }
+
+    /// Ticks top screen, returning `false` once app should exit
+    pub fn on_tick(&mut self, hid: &dyn HidSink) -> bool {
+        let Some(&index) = self.stack.last() else {
+            return false;
+        };
+        let transition = self.screens[index].1.on_tick(&mut self.app, hid);
+        self.apply(transition, hid)
+    }
+
+    fn find(&self, id: ScreenId) -> Option<usize> {
+        self.screens.iter().position(|(other, _)| *other == id)
+    }
+
+    /// Goes where `transition` says, returning `false` once app should
+    /// exit. Transitions to unknown screens are ignored.
+    fn apply(&mut self, transition: Transition, hid: &dyn HidSink) -> bool {
+        let next = match transition {
+            Transition::Stay => return true,
+            Transition::Push(id) | Transition::Replace(id) => {
+                let Some(next) = self.find(id) else {
+                    return true;
+                };
+                Some(next)
+            }
+            Transition::Pop | Transition::Exit => None,
+        };
+        if let Some(&index) = self.stack.last() {
+            self.screens[index].1.on_leave(&mut self.app, hid);
+        }
+        match transition {
+            Transition::Push(_) => (),
+            Transition::Exit => self.stack.clear(),
+            _ => {
+                self.stack.pop();
+            }
+        }
+        self.stack.extend(next);
+        match self.stack.last() {
+            Some(&index) => {
+                self.screens[index].1.on_enter(&mut self.app, hid);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bt::Key,
+        hid::{HidEvent, RecordingHid},
+        macros::Macros,
+        view_port::{InputKey, InputType},
+    };
+
+    /// Presses its key on host while it's top screen, going where input
+    /// key says
+    struct KeyScreen(Key);
+
+    impl Screen for KeyScreen {
+        fn draw(&self, _app: &App, _canvas: &mut CanvasView<'_>) {}
+
+        fn on_input(
+            &mut self,
+            _app: &mut App,
+            event: &InputEvent,
+            _hid: &dyn HidSink,
+        ) -> Transition {
+            match event.key {
+                InputKey::Up => Transition::Push("b"),
+                InputKey::Right => Transition::Replace("c"),
+                InputKey::Down => Transition::Pop,
+                InputKey::Back => Transition::Exit,
+                InputKey::Ok => Transition::Push("missing"),
+                InputKey::Left => Transition::Stay,
+            }
+        }
+
+        fn on_enter(&mut self, _app: &mut App, hid: &dyn HidSink) {
+            hid.key_press(self.0.into()).unwrap();
+        }
+
+        fn on_leave(&mut self, _app: &mut App, hid: &dyn HidSink) {
+            hid.key_release(self.0.into()).unwrap();
+        }
+    }
+
+    fn navigator(events: &MessageQueue<InputEvent>) -> Navigator<'_> {
+        let mut navigator = Navigator::new(App {
+            events,
+            profiles: Profiles::builtin(),
+            profile: 0,
+            imu: None,
+            gestures_enabled: false,
+            settings: Settings::default(),
+            history: History::default(),
+            macros: Macros::default(),
+            recorder: None,
+            switch_host: None,
+        });
+        navigator.register("a", KeyScreen(Key::A));
+        navigator.register("b", KeyScreen(Key::B));
+        navigator.register("c", KeyScreen(Key::C));
+        navigator
+    }
+
+    fn input(navigator: &mut Navigator, key: InputKey, hid: &RecordingHid) {
+        let event = InputEvent {
+            key,
+            type_: InputType::Short,
+        };
+        navigator.on_input(&event, hid);
+    }
+
+    fn press(key: Key) -> HidEvent {
+        HidEvent::KeyPress(key.into())
+    }
+
+    fn release(key: Key) -> HidEvent {
+        HidEvent::KeyRelease(key.into())
+    }
+
+    #[test]
+    fn push_and_pop() {
+        let events = MessageQueue::default();
+        let mut navigator = navigator(&events);
+        let hid = RecordingHid::new();
+        assert_eq!(navigator.current(), None);
+        assert!(navigator.start("a", &hid));
+        assert_eq!(navigator.current(), Some("a"));
+        assert_eq!(hid.take(), [press(Key::A)]);
+
+        input(&mut navigator, InputKey::Up, &hid);
+        assert_eq!(navigator.current(), Some("b"));
+        assert_eq!(hid.take(), [release(Key::A), press(Key::B)]);
+
+        input(&mut navigator, InputKey::Down, &hid);
+        assert_eq!(navigator.current(), Some("a"));
+        assert_eq!(hid.take(), [release(Key::B), press(Key::A)]);
+    }
+
+    #[test]
+    fn popping_last_screen_exits() {
+        let events = MessageQueue::default();
+        let mut navigator = navigator(&events);
+        let hid = RecordingHid::new();
+        navigator.start("a", &hid);
+        hid.clear();
+        let event = InputEvent {
+            key: InputKey::Down,
+            type_: InputType::Short,
+        };
+        assert!(!navigator.on_input(&event, &hid));
+        assert_eq!(navigator.current(), None);
+        assert_eq!(hid.take(), [release(Key::A)]);
+        assert!(!navigator.on_input(&event, &hid));
+        assert!(!navigator.on_tick(&hid));
+        assert_eq!(hid.take(), []);
+    }
+
+    #[test]
+    fn replace_swaps_top_screen() {
+        let events = MessageQueue::default();
+        let mut navigator = navigator(&events);
+        let hid = RecordingHid::new();
+        navigator.start("a", &hid);
+        input(&mut navigator, InputKey::Up, &hid);
+        hid.clear();
+
+        input(&mut navigator, InputKey::Right, &hid);
+        assert_eq!(navigator.current(), Some("c"));
+        assert_eq!(hid.take(), [release(Key::B), press(Key::C)]);
+
+        input(&mut navigator, InputKey::Down, &hid);
+        assert_eq!(navigator.current(), Some("a"));
+        assert_eq!(hid.take(), [release(Key::C), press(Key::A)]);
+    }
+
+    #[test]
+    fn exit_leaves_only_top_screen() {
+        let events = MessageQueue::default();
+        let mut navigator = navigator(&events);
+        let hid = RecordingHid::new();
+        navigator.start("a", &hid);
+        input(&mut navigator, InputKey::Up, &hid);
+        hid.clear();
+        let event = InputEvent {
+            key: InputKey::Back,
+            type_: InputType::Short,
+        };
+        assert!(!navigator.on_input(&event, &hid));
+        assert_eq!(navigator.current(), None);
+        assert_eq!(hid.take(), [release(Key::B)]);
+    }
+
+    #[test]
+    fn stay_and_unknown_screens_do_nothing() {
+        let events = MessageQueue::default();
+        let mut navigator = navigator(&events);
+        let hid = RecordingHid::new();
+        assert!(!navigator.start("missing", &hid));
+        assert_eq!(navigator.current(), None);
+        navigator.start("a", &hid);
+        hid.clear();
+        for key in [InputKey::Left, InputKey::Ok] {
+            input(&mut navigator, key, &hid);
+            assert_eq!(navigator.current(), Some("a"));
+        }
+        assert!(navigator.on_tick(&hid));
+        assert_eq!(hid.take(), []);
+    }
+
+    #[test]
+    fn register_replaces_same_id() {
+        let events = MessageQueue::default();
+        let mut navigator = navigator(&events);
+        let hid = RecordingHid::new();
+        navigator.register("a", KeyScreen(Key::Z));
+        navigator.start("a", &hid);
+        assert_eq!(hid.take(), [press(Key::Z)]);
+    }
+
+    #[test]
+    fn set_profile_remembers_name() {
+        let events = MessageQueue::default();
+        let mut navigator = navigator(&events);
+        let app = navigator.app_mut();
+        app.set_profile(1);
+        assert_eq!(app.profile, 1);
+        let name = app.profiles.get(1).unwrap().name.to_str().unwrap();
+        assert_eq!(app.settings.profile.as_deref(), Some(name));
+    }
+
+    #[test]
+    fn or_keeps_first_unless_stay() {
+        let pop = Transition::Pop;
+        assert_eq!(Transition::Stay.or(pop), pop);
+        assert_eq!(Transition::Exit.or(pop), Transition::Exit);
+        assert_eq!(Transition::Stay.or(Transition::Stay), Transition::Stay);
+    }
+}
@@ -0,0 +1,80 @@
+use flipperzero::gui::{canvas::CanvasView, view_port::InputEvent};
+
+use crate::{
+    hid::HidSink,
+    icons,
+    imu::air_mouse::{AirMouse, AirMouseConfig},
+    keymap::{Action, Mode},
+    mouse,
+    screen::{App, Screen, ScreenId, Transition},
+};
+
+use super::{run_actions, send};
+
+/// Moves cursor by tilting device, needs IMU
+pub struct AirMouseScreen {
+    air_mouse: AirMouse,
+}
+
+impl AirMouseScreen {
+    pub const ID: ScreenId = "air_mouse";
+
+    pub fn new() -> Self {
+        Self {
+            air_mouse: AirMouse::new(AirMouseConfig::default()),
+        }
+    }
+}
+
+impl Default for AirMouseScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen for AirMouseScreen {
+    fn draw(&self, _app: &App, canvas: &mut CanvasView<'_>) {
+        canvas.draw_icon(54, 2, &icons::AIR_MOUSE_MODE);
+        if self.air_mouse.is_calibrating() {
+            canvas.draw_str(2, 30, c"Hold still");
+        }
+    }
+
+    fn on_input(
+        &mut self,
+        app: &mut App,
+        event: &InputEvent,
+        hid: &dyn HidSink,
+    ) -> Transition {
+        let mut actions =
+            app.keymap().map(event.key, event.type_, Mode::AirMouse);
+        actions.retain(|action| match action {
+            Action::Recenter => {
+                self.air_mouse.recenter();
+                false
+            }
+            _ => true,
+        });
+        run_actions(app, actions, hid)
+    }
+
+    fn on_tick(&mut self, app: &mut App, hid: &dyn HidSink) -> Transition {
+        let Some(imu) = &mut app.imu else {
+            return Transition::Replace(super::BasicScreen::ID);
+        };
+        if let Some((dx, dy)) = self.air_mouse.poll(imu) {
+            for (dx, dy) in mouse::split_delta(dx, dy) {
+                send(app, Action::MouseMove(dx, dy), hid);
+            }
+        }
+        Transition::Stay
+    }
+
+    fn on_enter(&mut self, _app: &mut App, _hid: &dyn HidSink) {
+        self.air_mouse.recenter();
+    }
+
+    fn on_leave(&mut self, _app: &mut App, hid: &dyn HidSink) {
+        let _ = hid.mouse_release_all();
+    }
+}
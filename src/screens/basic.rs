@@ -0,0 +1,139 @@
+use alloc::vec::Vec;
+
+use flipperzero::gui::{canvas::CanvasView, view_port::InputEvent};
+use flipperzero_sys as sys;
+
+use crate::{
+    hid::HidSink,
+    icons,
+    imu::gesture::{GestureConfig, GestureRecognizer},
+    keymap::{Action, Keymap},
+    pattern::{PatternConfig, PatternRecognizer, Trigger},
+    repeat::{HoldRepeat, RepeatConfig},
+    screen::{App, Screen, ScreenId, Transition},
+};
+
+use super::run_actions;
+
+/// Media controls, with patterns, hold-to-repeat and gestures
+pub struct BasicScreen {
+    hold_repeat: HoldRepeat,
+    patterns: PatternRecognizer,
+    gestures: GestureRecognizer,
+}
+
+impl BasicScreen {
+    pub const ID: ScreenId = "basic";
+
+    pub fn new() -> Self {
+        Self {
+            hold_repeat: HoldRepeat::new(RepeatConfig::default()),
+            patterns: PatternRecognizer::new(PatternConfig::default()),
+            gestures: GestureRecognizer::new(GestureConfig::default()),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.hold_repeat.reset();
+        self.patterns.reset();
+        self.gestures.reset();
+    }
+}
+
+impl Default for BasicScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen for BasicScreen {
+    fn draw(&self, app: &App, canvas: &mut CanvasView<'_>) {
+        let (x, y) = (32, (128 + 15) / 2);
+        canvas.draw_str(17, 10, app.profile_name());
+        canvas.draw_icon(53, 2, &icons::BASIC_MODE);
+        canvas.draw_circle(x, y, 5);
+        canvas.draw_icon(x - 2, y - 2, &icons::PLAY_PAUSE);
+        canvas.draw_circle(x, y, 16);
+        canvas.draw_icon(x - 14, y - 3, &icons::LEFT_ARROW);
+        canvas.draw_icon(x + 7, y - 3, &icons::RIGHT_ARROW);
+        canvas.draw_icon(x - 4, y - 13, &icons::FRAME_LEFT);
+        canvas.draw_icon(x - 4, y + 7, &icons::FRAME_RIGHT);
+        canvas.draw_circle(x, y, 25);
+        canvas.draw_icon(x - 23, y - 2, &icons::SPEED_LEFT);
+        canvas.draw_icon(x + 18, y - 2, &icons::SPEED_RIGHT);
+        canvas.draw_icon(x - 3, y - 23, &icons::VOLUME_UP);
+        canvas.draw_icon(x - 3, y + 18, &icons::VOLUME_DOWN);
+    }
+
+    fn on_input(
+        &mut self,
+        app: &mut App,
+        event: &InputEvent,
+        hid: &dyn HidSink,
+    ) -> Transition {
+        let now = unsafe { sys::furi_get_tick() };
+        let keymap = app.keymap();
+        let actions = self
+            .patterns
+            .on_input(keymap, event.key, event.type_, now)
+            .into_iter()
+            .flat_map(|trigger| {
+                basic_actions(&mut self.hold_repeat, keymap, trigger, now)
+            })
+            .collect();
+        run_actions(app, actions, hid)
+    }
+
+    fn on_tick(&mut self, app: &mut App, hid: &dyn HidSink) -> Transition {
+        let now = unsafe { sys::furi_get_tick() };
+        let gestures = match &mut app.imu {
+            Some(imu) if app.gestures_enabled => self.gestures.poll(imu),
+            _ => Vec::new(),
+        };
+        let keymap = app.keymap();
+        let mut actions: Vec<Action> = self
+            .patterns
+            .tick(keymap, now)
+            .into_iter()
+            .flat_map(|trigger| {
+                basic_actions(&mut self.hold_repeat, keymap, trigger, now)
+            })
+            .collect();
+        actions.extend(self.hold_repeat.tick(keymap, now));
+        for gesture in gestures {
+            actions.extend(gesture.actions(keymap));
+        }
+        run_actions(app, actions, hid)
+    }
+
+    // profile, gestures or settings might have changed while covered
+    fn on_enter(&mut self, app: &mut App, _hid: &dyn HidSink) {
+        self.reset();
+        self.hold_repeat.set_config(app.settings.repeat_config());
+        self.patterns.set_config(app.settings.pattern_config());
+    }
+
+    fn on_leave(&mut self, _app: &mut App, _hid: &dyn HidSink) {
+        self.reset();
+    }
+}
+
+/// Maps Basic mode trigger to actions, plain inputs going through
+/// hold-to-repeat
+fn basic_actions(
+    hold_repeat: &mut HoldRepeat,
+    keymap: &Keymap,
+    trigger: Trigger,
+    now: u32,
+) -> Vec<Action> {
+    match trigger {
+        Trigger::Input(key, type_) => {
+            hold_repeat.on_input(keymap, key, type_, now)
+        }
+        Trigger::Pattern(pattern) => {
+            // inputs pattern is made of are done
+            hold_repeat.reset();
+            keymap.get_pattern(pattern).into_iter().collect()
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use core::ffi::CStr;
+
+use alloc::{ffi::CString, format, string::String, vec::Vec};
+
+use flipperzero::gui::{canvas::CanvasView, view_port::InputEvent};
+
+use crate::{
+    hid::HidSink,
+    hosts::{Host, MAX_HOST_NAME_LEN},
+    screen::{App, Screen, ScreenId, Transition},
+    storage::Storage,
+    views,
+};
+
+/// Picks host to connect to, in firmware's submenu. Active host is marked
+/// with `*`, picking it again renames it.
+pub struct HostsScreen;
+
+impl HostsScreen {
+    pub const ID: ScreenId = "hosts";
+}
+
+impl Screen for HostsScreen {
+    // covered by submenu for as long as it's open
+    fn draw(&self, _app: &App, _canvas: &mut CanvasView<'_>) {}
+
+    fn on_input(
+        &mut self,
+        _app: &mut App,
+        _event: &InputEvent,
+        _hid: &dyn HidSink,
+    ) -> Transition {
+        Transition::Stay
+    }
+
+    /// Submenu is opened on first tick, as it blocks until user is done
+    fn on_tick(&mut self, app: &mut App, _hid: &dyn HidSink) -> Transition {
+        loop {
+            let names: Vec<CString> = app
+                .settings
+                .hosts
+                .iter()
+                .enumerate()
+                .map(|(i, host)| {
+                    let mark = if i == app.settings.host { "* " } else { "" };
+                    CString::new(format!("{mark}{}", host.name))
+                        .unwrap_or_default()
+                })
+                .collect();
+            let names: Vec<&CStr> =
+                names.iter().map(|name| name.as_c_str()).collect();
+            let Some(picked) = views::submenu(c"Hosts", &names) else {
+                return Transition::Pop;
+            };
+
+            if picked != app.settings.host {
+                app.settings.host = picked;
+                let _ = app.settings.save(&Storage::open());
+                app.switch_host = Some(picked);
+                return Transition::Pop;
+            }
+
+            let name = views::text_input(c"Host name", MAX_HOST_NAME_LEN);
+            // `#` would start comment in settings file
+            let name = name
+                .map(|name| String::from(name.replace('#', "").trim()))
+                .filter(|name| Host::is_valid_name(name));
+            if let Some(name) = name {
+                app.settings.hosts[picked].name = name;
+                let _ = app.settings.save(&Storage::open());
+            }
+        }
+    }
+}
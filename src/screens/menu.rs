@@ -0,0 +1,259 @@
+use core::{ffi::CStr, iter};
+
+use alloc::{ffi::CString, string::String, vec::Vec};
+
+use flipperzero::{
+    furi::{message_queue::MessageQueue, time::FuriDuration},
+    gui::{
+        canvas::CanvasView,
+        view_port::{InputEvent, InputKey, InputType},
+    },
+    println,
+};
+use flipperzero_sys as sys;
+
+use crate::{
+    hid::HidSink,
+    macros::{self, Recorder},
+    menu::{Menu, MenuEvent},
+    screen::{App, Screen, ScreenId, Transition},
+    search::{self, History},
+    storage::Storage,
+    views,
+};
+
+use super::{HostsScreen, SettingsScreen, UnpairScreen};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MenuItem {
+    Profile,
+    Search,
+    Macros,
+    Record,
+    Gestures,
+    Settings,
+    Hosts,
+    Unpair,
+    Exit,
+}
+
+const MENU_ITEMS: &[MenuItem] = &[
+    MenuItem::Profile,
+    MenuItem::Search,
+    MenuItem::Macros,
+    MenuItem::Record,
+    MenuItem::Gestures,
+    MenuItem::Settings,
+    MenuItem::Hosts,
+    MenuItem::Unpair,
+    MenuItem::Exit,
+];
+
+/// Main menu, opened on top of mode screens
+pub struct MenuScreen {
+    menu: Menu<'static, MenuItem>,
+}
+
+impl MenuScreen {
+    pub const ID: ScreenId = "menu";
+
+    pub fn new() -> Self {
+        Self {
+            menu: Menu::new(MENU_ITEMS),
+        }
+    }
+}
+
+impl Default for MenuScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen for MenuScreen {
+    fn draw(&self, app: &App, canvas: &mut CanvasView<'_>) {
+        for (i, item) in MENU_ITEMS.iter().enumerate() {
+            let y = 16 + i as i32 * 12;
+            if i == self.menu.cursor() {
+                canvas.draw_frame(0, y, 64, 12);
+            }
+            match item {
+                MenuItem::Profile => {
+                    canvas.draw_str(2, y + 9, c"<");
+                    canvas.draw_str(10, y + 9, app.profile_name());
+                    canvas.draw_str(58, y + 9, c">");
+                }
+                MenuItem::Search => canvas.draw_str(10, y + 9, c"Search"),
+                MenuItem::Macros => canvas.draw_str(10, y + 9, c"Macros"),
+                MenuItem::Record => {
+                    let text = if app.recorder.is_some() {
+                        c"Stop rec"
+                    } else {
+                        c"Record"
+                    };
+                    canvas.draw_str(10, y + 9, text)
+                }
+                MenuItem::Gestures => {
+                    let text = if app.gestures_enabled {
+                        c"Gest: on"
+                    } else {
+                        c"Gest: off"
+                    };
+                    canvas.draw_str(10, y + 9, text)
+                }
+                MenuItem::Settings => canvas.draw_str(10, y + 9, c"Settings"),
+                MenuItem::Hosts => canvas.draw_str(10, y + 9, c"Hosts"),
+                MenuItem::Unpair => canvas.draw_str(10, y + 9, c"Unpair"),
+                MenuItem::Exit => canvas.draw_str(10, y + 9, c"Exit"),
+            }
+        }
+    }
+
+    fn on_input(
+        &mut self,
+        app: &mut App,
+        event: &InputEvent,
+        hid: &dyn HidSink,
+    ) -> Transition {
+        match self.menu.on_input(event.key, event.type_) {
+            Some(MenuEvent::Close) => Transition::Pop,
+            Some(MenuEvent::Exit | MenuEvent::Select(MenuItem::Exit)) => {
+                Transition::Exit
+            }
+            Some(MenuEvent::Select(MenuItem::Profile)) => {
+                app.set_profile(app.profiles.cycle(app.profile, 1));
+                Transition::Stay
+            }
+            Some(MenuEvent::Adjust(MenuItem::Profile, delta)) => {
+                app.set_profile(app.profiles.cycle(app.profile, delta));
+                Transition::Stay
+            }
+            Some(
+                MenuEvent::Select(MenuItem::Gestures)
+                | MenuEvent::Adjust(MenuItem::Gestures, _),
+            ) => {
+                app.gestures_enabled =
+                    !app.gestures_enabled && app.imu.is_some();
+                Transition::Stay
+            }
+            Some(MenuEvent::Select(MenuItem::Search)) => {
+                let Some(query) = pick_query(&app.history) else {
+                    return Transition::Stay;
+                };
+                app.history.push(&query);
+                let _ = app.history.save(&Storage::open());
+                let layout = app.settings.layout();
+                let res = search::search(&hid, layout, &query, || {
+                    typing_pace(app.events)
+                });
+                if let Err(err) = res {
+                    println!("search: {:?}\r", err);
+                }
+                Transition::Pop
+            }
+            Some(MenuEvent::Select(MenuItem::Macros)) => {
+                let picked = views::submenu(c"Macros", &app.macros.names());
+                let Some(macro_) = picked.and_then(|i| app.macros.get(i))
+                else {
+                    return Transition::Stay;
+                };
+                let res = macro_
+                    .play(&hid, |delay_ms| macro_wait(app.events, delay_ms));
+                if let Err(err) = res {
+                    println!("macro: {:?}\r", err);
+                }
+                Transition::Pop
+            }
+            Some(MenuEvent::Select(MenuItem::Record)) => {
+                match app.recorder.take() {
+                    None => {
+                        app.recorder = Some(Recorder::new());
+                        return Transition::Pop;
+                    }
+                    Some(recorder) if recorder.steps().is_empty() => (),
+                    Some(recorder) => {
+                        let name = views::text_input(
+                            c"Macro name",
+                            macros::MAX_NAME_LEN,
+                        );
+                        // `#` would start comment in macros file
+                        let name = name
+                            .map(|name| name.replace('#', ""))
+                            .and_then(|name| CString::new(name).ok());
+                        if let Some(name) = name {
+                            app.macros.add(recorder.finish(name));
+                            let _ = app.macros.save(&Storage::open());
+                        }
+                    }
+                }
+                Transition::Stay
+            }
+            Some(MenuEvent::Select(MenuItem::Settings)) => {
+                Transition::Push(SettingsScreen::ID)
+            }
+            Some(MenuEvent::Select(MenuItem::Hosts)) => {
+                Transition::Push(HostsScreen::ID)
+            }
+            Some(MenuEvent::Select(MenuItem::Unpair)) => {
+                Transition::Push(UnpairScreen::ID)
+            }
+            Some(MenuEvent::Adjust(
+                MenuItem::Search
+                | MenuItem::Macros
+                | MenuItem::Record
+                | MenuItem::Settings
+                | MenuItem::Hosts
+                | MenuItem::Unpair
+                | MenuItem::Exit,
+                _,
+            ))
+            | None => Transition::Stay,
+        }
+    }
+}
+
+/// Lets user enter new query, or pick one from history
+fn pick_query(history: &History) -> Option<String> {
+    if history.queries().is_empty() {
+        return views::text_input(c"Search YouTube", search::MAX_QUERY_LEN);
+    }
+    let items: Vec<&CStr> = iter::once(c"New search")
+        .chain(history.queries().iter().map(|q| q.as_c_str()))
+        .collect();
+    match views::submenu(c"Search YouTube", &items)? {
+        0 => views::text_input(c"Search YouTube", search::MAX_QUERY_LEN),
+        i => history.queries()[i - 1].to_str().ok().map(String::from),
+    }
+}
+
+/// Waits between typed key reports, pressing Back cancels typing
+fn typing_pace(event_queue: &MessageQueue<InputEvent>) -> bool {
+    match event_queue.get(FuriDuration::from_secs(1) / 100) {
+        Ok(event) => !is_back_press(&event),
+        Err(_) => true,
+    }
+}
+
+/// Short Back press, checked on its [`InputType::Short`] event, so that no
+/// part of it is left in queue to be handled by main loop
+fn is_back_press(event: &InputEvent) -> bool {
+    (event.key, event.type_) == (InputKey::Back, InputType::Short)
+}
+
+/// Waits `delay_ms` before next step of replayed macro, pressing Back
+/// cancels replay
+fn macro_wait(event_queue: &MessageQueue<InputEvent>, delay_ms: u32) -> bool {
+    let start = unsafe { sys::furi_get_tick() };
+    loop {
+        let elapsed = unsafe { sys::furi_get_tick() }.wrapping_sub(start);
+        if elapsed >= delay_ms {
+            return true;
+        }
+        let timeout = FuriDuration::from_millis((delay_ms - elapsed).into());
+        if let Ok(event) = event_queue.get(timeout) {
+            if is_back_press(&event) {
+                return false;
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! App's own screens
+
+use alloc::vec::Vec;
+
+use crate::{
+    hid::HidSink,
+    keymap::{Action, Mode},
+    screen::{App, Navigator, ScreenId, Transition},
+};
+
+pub mod air_mouse;
+pub mod basic;
+pub mod hosts;
+pub mod menu;
+pub mod mouse;
+pub mod settings;
+pub mod unpair;
+
+pub use self::{
+    air_mouse::AirMouseScreen, basic::BasicScreen, hosts::HostsScreen,
+    menu::MenuScreen, mouse::MouseScreen, mouse::ScrollScreen,
+    settings::SettingsScreen, unpair::UnpairScreen,
+};
+
+/// Registers all of app's screens
+pub fn register_all(navigator: &mut Navigator) {
+    navigator.register(BasicScreen::ID, BasicScreen::new());
+    navigator.register(MouseScreen::ID, MouseScreen::new());
+    navigator.register(ScrollScreen::ID, ScrollScreen::new());
+    navigator.register(AirMouseScreen::ID, AirMouseScreen::new());
+    navigator.register(MenuScreen::ID, MenuScreen::new());
+    navigator.register(SettingsScreen::ID, SettingsScreen);
+    navigator.register(HostsScreen::ID, HostsScreen);
+    navigator.register(UnpairScreen::ID, UnpairScreen);
+}
+
+/// Screen of given mode
+pub fn mode_screen(mode: Mode) -> ScreenId {
+    match mode {
+        Mode::Basic => BasicScreen::ID,
+        Mode::Mouse => MouseScreen::ID,
+        Mode::Scroll => ScrollScreen::ID,
+        Mode::AirMouse => AirMouseScreen::ID,
+    }
+}
+
+/// Handles actions that do the same in every mode, recording and sending
+/// ones that go to host
+fn run_actions(
+    app: &mut App,
+    actions: Vec<Action>,
+    hid: &dyn HidSink,
+) -> Transition {
+    let mut transition = Transition::Stay;
+    for action in actions {
+        let next = match action {
+            Action::SwitchMode(Mode::AirMouse) if app.imu.is_none() => {
+                Transition::Replace(BasicScreen::ID)
+            }
+            Action::SwitchMode(mode) => Transition::Replace(mode_screen(mode)),
+            Action::OpenMenu => Transition::Push(MenuScreen::ID),
+            action => {
+                send(app, action, hid);
+                Transition::Stay
+            }
+        };
+        transition = transition.or(next);
+    }
+    transition
+}
+
+/// Records and sends action that goes to host, like cursor or wheel motion
+fn send(app: &mut App, action: Action, hid: &dyn HidSink) {
+    app.record(action);
+    let _ = action.send(&hid);
+}
@@ -0,0 +1,220 @@
+use alloc::vec::Vec;
+
+use flipperzero::gui::{canvas::CanvasView, view_port::InputEvent};
+use flipperzero_sys as sys;
+
+use crate::{
+    bt::MouseButton,
+    hid::HidSink,
+    icons,
+    keymap::{self, Action, Keymap, Mode},
+    mouse::{self, MotionConfig, MouseMotion, ScrollMotion},
+    pattern::{PatternConfig, PatternRecognizer, Trigger},
+    screen::{App, Screen, ScreenId, Transition},
+};
+
+use super::{run_actions, send};
+
+/// Moves cursor with directional inputs, clicks with Ok, see
+/// [`keymap::mouse_patterns`] for the rest of buttons
+pub struct MouseScreen {
+    motion: MouseMotion,
+    patterns: PatternRecognizer,
+    mouse_patterns: Keymap,
+    drag_lock: bool,
+    last_tick: u32,
+}
+
+impl MouseScreen {
+    pub const ID: ScreenId = "mouse";
+
+    pub fn new() -> Self {
+        Self {
+            motion: MouseMotion::new(MotionConfig::default()),
+            patterns: PatternRecognizer::new(PatternConfig::default()),
+            mouse_patterns: keymap::mouse_patterns(),
+            drag_lock: false,
+            last_tick: 0,
+        }
+    }
+
+    /// Maps triggers to actions, handling motion and drag lock
+    fn run_triggers(
+        &mut self,
+        app: &mut App,
+        triggers: Vec<Trigger>,
+        hid: &dyn HidSink,
+    ) -> Transition {
+        let mut actions = Vec::new();
+        for trigger in triggers {
+            match trigger {
+                Trigger::Input(key, type_) => {
+                    actions.extend(app.keymap().map(key, type_, Mode::Mouse))
+                }
+                Trigger::Pattern(pattern) => {
+                    actions.extend(self.mouse_patterns.get_pattern(pattern))
+                }
+            }
+        }
+        actions.retain_mut(|action| match *action {
+            Action::StartMove(direction) => {
+                self.motion.press(direction);
+                false
+            }
+            Action::StopMove(direction) => {
+                self.motion.release(direction);
+                false
+            }
+            Action::ToggleDragLock => {
+                self.drag_lock = !self.drag_lock;
+                *action = if self.drag_lock {
+                    Action::MousePress(MouseButton::M1)
+                } else {
+                    Action::MouseRelease(MouseButton::M1)
+                };
+                true
+            }
+            // releasing left button ends drag lock
+            Action::MouseClick(MouseButton::M1) => {
+                self.drag_lock = false;
+                true
+            }
+            _ => true,
+        });
+        run_actions(app, actions, hid)
+    }
+}
+
+impl Default for MouseScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen for MouseScreen {
+    fn draw(&self, _app: &App, canvas: &mut CanvasView<'_>) {
+        canvas.draw_icon(54, 2, &icons::MOUSE_MODE);
+        if self.drag_lock {
+            canvas.draw_str(17, 10, c"Drag");
+        }
+    }
+
+    fn on_input(
+        &mut self,
+        app: &mut App,
+        event: &InputEvent,
+        hid: &dyn HidSink,
+    ) -> Transition {
+        let now = unsafe { sys::furi_get_tick() };
+        let triggers = self.patterns.on_input(
+            &self.mouse_patterns,
+            event.key,
+            event.type_,
+            now,
+        );
+        self.run_triggers(app, triggers, hid)
+    }
+
+    fn on_tick(&mut self, app: &mut App, hid: &dyn HidSink) -> Transition {
+        let now = unsafe { sys::furi_get_tick() };
+        let triggers = self.patterns.tick(&self.mouse_patterns, now);
+        let transition = self.run_triggers(app, triggers, hid);
+        if !self.motion.is_moving() {
+            self.last_tick = now;
+        }
+        while now.wrapping_sub(self.last_tick) >= mouse::TICK_MS {
+            self.last_tick = self.last_tick.wrapping_add(mouse::TICK_MS);
+            if let Some((dx, dy)) = self.motion.tick() {
+                for (dx, dy) in mouse::split_delta(dx, dy) {
+                    send(app, Action::MouseMove(dx, dy), hid);
+                }
+            }
+        }
+        transition
+    }
+
+    fn on_enter(&mut self, app: &mut App, _hid: &dyn HidSink) {
+        self.patterns.reset();
+        self.patterns.set_config(app.settings.pattern_config());
+        self.motion.set_config(app.settings.motion_config());
+    }
+
+    fn on_leave(&mut self, _app: &mut App, hid: &dyn HidSink) {
+        self.patterns.reset();
+        self.motion.stop();
+        self.drag_lock = false;
+        let _ = hid.mouse_release_all();
+    }
+}
+
+/// Scrolls with Up/Down
+pub struct ScrollScreen {
+    scroll: ScrollMotion,
+    last_tick: u32,
+}
+
+impl ScrollScreen {
+    pub const ID: ScreenId = "scroll";
+
+    pub fn new() -> Self {
+        Self {
+            scroll: ScrollMotion::new(ScrollMotion::default_config()),
+            last_tick: 0,
+        }
+    }
+}
+
+impl Default for ScrollScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen for ScrollScreen {
+    fn draw(&self, _app: &App, canvas: &mut CanvasView<'_>) {
+        canvas.draw_icon(54, 2, &icons::SCROLL_MODE);
+    }
+
+    fn on_input(
+        &mut self,
+        app: &mut App,
+        event: &InputEvent,
+        hid: &dyn HidSink,
+    ) -> Transition {
+        let mut actions =
+            app.keymap().map(event.key, event.type_, Mode::Scroll);
+        actions.retain(|action| match *action {
+            Action::StartScroll(direction) => {
+                self.scroll.press(direction);
+                false
+            }
+            Action::StopScroll(direction) => {
+                self.scroll.release(direction);
+                false
+            }
+            _ => true,
+        });
+        run_actions(app, actions, hid)
+    }
+
+    fn on_tick(&mut self, app: &mut App, hid: &dyn HidSink) -> Transition {
+        let now = unsafe { sys::furi_get_tick() };
+        if !self.scroll.is_scrolling() {
+            self.last_tick = now;
+        }
+        while now.wrapping_sub(self.last_tick) >= mouse::TICK_MS {
+            self.last_tick = self.last_tick.wrapping_add(mouse::TICK_MS);
+            if let Some(delta) = self.scroll.tick() {
+                for (delta, _) in mouse::split_delta(delta, 0) {
+                    send(app, Action::MouseScroll(delta), hid);
+                }
+            }
+        }
+        Transition::Stay
+    }
+
+    fn on_leave(&mut self, _app: &mut App, hid: &dyn HidSink) {
+        self.scroll.stop();
+        let _ = hid.mouse_release_all();
+    }
+}
@@ -0,0 +1,260 @@
+use core::ffi::CStr;
+
+use alloc::{ffi::CString, format, vec::Vec};
+
+use flipperzero::gui::{canvas::CanvasView, view_port::InputEvent};
+
+use crate::{
+    bt::{HidProfileParams, MAX_DEVICE_NAME_LEN},
+    hid::HidSink,
+    screen::{App, Screen, ScreenId, Transition},
+    settings::{
+        ACCEL_CURVES, MAX_MOUSE_SPEED, ORIENTATIONS, REPEAT_RATES, Settings,
+    },
+    storage::Storage,
+    typing::LAYOUTS,
+    views::{self, ListItem},
+};
+
+const MOUSE_SPEEDS: &[&CStr] = &[c"1", c"2", c"3", c"4", c"5"];
+const ON_OFF: &[&CStr] = &[c"Off", c"On"];
+
+// order of items in list
+const PROFILE: usize = 0;
+const MOUSE_SPEED: usize = 1;
+const ACCEL_CURVE: usize = 2;
+const REPEAT_RATE: usize = 3;
+const ORIENTATION: usize = 4;
+const HAPTIC: usize = 5;
+const LED: usize = 6;
+const LAYOUT: usize = 7;
+const TAP_GAP: usize = 8;
+const CHORD_WINDOW: usize = 9;
+const VERY_LONG: usize = 10;
+const DEVICE_NAME: usize = 11;
+const MAC_XOR: usize = 12;
+
+// values pattern timeouts can be set to here, others only in settings file
+const TAP_GAPS_MS: &[u32] = &[200, 250, 300, 400, 500];
+const CHORD_WINDOWS_MS: &[u32] = &[100, 150, 200, 300];
+const VERY_LONGS_MS: &[u32] = &[1500, 2000, 3000, 5000];
+
+/// Edits [`Settings`] in firmware's variable item list, saving them once
+/// user backs out of it
+pub struct SettingsScreen;
+
+impl SettingsScreen {
+    pub const ID: ScreenId = "settings";
+}
+
+impl Screen for SettingsScreen {
+    // covered by item list for as long as it's open
+    fn draw(&self, _app: &App, _canvas: &mut CanvasView<'_>) {}
+
+    fn on_input(
+        &mut self,
+        _app: &mut App,
+        _event: &InputEvent,
+        _hid: &dyn HidSink,
+    ) -> Transition {
+        Transition::Stay
+    }
+
+    /// Item list is opened on first tick, as it blocks until user is done
+    fn on_tick(&mut self, app: &mut App, _hid: &dyn HidSink) -> Transition {
+        let mut settings = app.settings.clone();
+        let mut profile = app.profile;
+        let mut selected = PROFILE;
+        loop {
+            let entered = edit(app, &mut settings, &mut profile, selected);
+            match entered {
+                Some(DEVICE_NAME) => {
+                    let name =
+                        views::text_input(c"Device name", MAX_DEVICE_NAME_LEN);
+                    // `#` would start comment in settings file
+                    let name =
+                        name.map(|name| name.replace('#', "")).filter(|name| {
+                            HidProfileParams::is_valid_device_name(name)
+                        });
+                    if let Some(name) = name {
+                        settings.device_name = name;
+                    }
+                    selected = DEVICE_NAME;
+                }
+                Some(item) => selected = item,
+                None => break,
+            }
+        }
+
+        let identity =
+            |s: &Settings| (s.device_name.clone(), s.active_host().mac_xor);
+        if identity(&settings) != identity(&app.settings) {
+            // host sees new identity only once profile restarts
+            app.switch_host = Some(settings.host);
+        }
+        if settings != app.settings {
+            app.settings = settings;
+            let _ = app.settings.save(&Storage::open());
+        }
+        app.set_profile(profile);
+        Transition::Pop
+    }
+}
+
+/// Shows settings list once, returning item Ok was pressed on
+fn edit(
+    app: &App,
+    settings: &mut Settings,
+    profile: &mut usize,
+    selected: usize,
+) -> Option<usize> {
+    let profiles: Vec<_> = (0..app.profiles.len())
+        .filter_map(|i| app.profiles.get(i))
+        .map(|p| p.name)
+        .collect();
+    let accel_curves: Vec<_> = ACCEL_CURVES.iter().map(|(_, n)| *n).collect();
+    let repeat_rates: Vec<_> = REPEAT_RATES.iter().map(|(_, n)| *n).collect();
+    let orientations: Vec<_> = ORIENTATIONS.iter().map(|(_, n)| *n).collect();
+    let layout_names: Vec<CString> = LAYOUTS
+        .iter()
+        .map(|(name, _)| CString::new(*name).unwrap())
+        .collect();
+    let layouts = as_c_strs(&layout_names);
+    let device_name =
+        CString::new(settings.device_name.as_str()).unwrap_or_default();
+    let device_names = [device_name.as_c_str()];
+    let tap_gaps = with_value(TAP_GAPS_MS, settings.tap_gap_ms);
+    let tap_gap_names = ms_names(&tap_gaps);
+    let tap_gap_names = as_c_strs(&tap_gap_names);
+    let chord_windows = with_value(CHORD_WINDOWS_MS, settings.chord_window_ms);
+    let chord_window_names = ms_names(&chord_windows);
+    let chord_window_names = as_c_strs(&chord_window_names);
+    let very_longs = with_value(VERY_LONGS_MS, settings.very_long_ms);
+    let very_long_names = ms_names(&very_longs);
+    let very_long_names = as_c_strs(&very_long_names);
+    // of active host, values past these can only be set in settings file
+    let mac_xor = settings.active_host().mac_xor;
+    let presets: Vec<u16> = (1..=16).collect();
+    let mac_xors = with_value(&presets, mac_xor);
+    let mac_xor_names: Vec<CString> = mac_xors
+        .iter()
+        .map(|xor| CString::new(format!("{xor}")).unwrap())
+        .collect();
+    let mac_xor_names = as_c_strs(&mac_xor_names);
+
+    let mut items = [
+        ListItem {
+            label: c"Profile",
+            values: &profiles,
+            index: *profile,
+        },
+        ListItem {
+            label: c"Mouse speed",
+            values: MOUSE_SPEEDS,
+            index: settings.mouse_speed as usize - 1,
+        },
+        ListItem {
+            label: c"Accel curve",
+            values: &accel_curves,
+            index: position(ACCEL_CURVES, settings.accel_curve),
+        },
+        ListItem {
+            label: c"Repeat rate",
+            values: &repeat_rates,
+            index: position(REPEAT_RATES, settings.repeat_rate),
+        },
+        ListItem {
+            label: c"Orientation",
+            values: &orientations,
+            index: position(ORIENTATIONS, settings.orientation),
+        },
+        ListItem {
+            label: c"Vibration",
+            values: ON_OFF,
+            index: settings.haptic as usize,
+        },
+        ListItem {
+            label: c"LED",
+            values: ON_OFF,
+            index: settings.led as usize,
+        },
+        ListItem {
+            label: c"Layout",
+            values: &layouts,
+            index: LAYOUTS
+                .iter()
+                .position(|(name, _)| *name == settings.layout)
+                .unwrap_or(0),
+        },
+        ListItem {
+            label: c"Tap gap",
+            values: &tap_gap_names,
+            index: position_of(&tap_gaps, settings.tap_gap_ms),
+        },
+        ListItem {
+            label: c"Chord window",
+            values: &chord_window_names,
+            index: position_of(&chord_windows, settings.chord_window_ms),
+        },
+        ListItem {
+            label: c"Very long hold",
+            values: &very_long_names,
+            index: position_of(&very_longs, settings.very_long_ms),
+        },
+        ListItem {
+            label: c"Device name",
+            values: &device_names,
+            index: 0,
+        },
+        ListItem {
+            label: c"MAC xor",
+            values: &mac_xor_names,
+            index: position_of(&mac_xors, mac_xor),
+        },
+    ];
+    let entered = views::variable_item_list(&mut items, selected);
+
+    *profile = items[PROFILE].index;
+    settings.mouse_speed =
+        (items[MOUSE_SPEED].index as u8 + 1).min(MAX_MOUSE_SPEED);
+    settings.accel_curve = ACCEL_CURVES[items[ACCEL_CURVE].index].0;
+    settings.repeat_rate = REPEAT_RATES[items[REPEAT_RATE].index].0;
+    settings.orientation = ORIENTATIONS[items[ORIENTATION].index].0;
+    settings.haptic = items[HAPTIC].index == 1;
+    settings.led = items[LED].index == 1;
+    settings.layout = LAYOUTS[items[LAYOUT].index].0;
+    settings.tap_gap_ms = tap_gaps[items[TAP_GAP].index];
+    settings.chord_window_ms = chord_windows[items[CHORD_WINDOW].index];
+    settings.very_long_ms = very_longs[items[VERY_LONG].index];
+    settings.hosts[settings.host].mac_xor = mac_xors[items[MAC_XOR].index];
+    entered
+}
+
+/// Index of `value` in `names`
+fn position<T: PartialEq>(names: &[(T, &CStr)], value: T) -> usize {
+    names.iter().position(|(v, _)| *v == value).unwrap_or(0)
+}
+
+/// `presets`, with `value` in front if it's not one of them
+fn with_value<T: Copy + PartialEq>(presets: &[T], value: T) -> Vec<T> {
+    let mut values = presets.to_vec();
+    if !values.contains(&value) {
+        values.insert(0, value);
+    }
+    values
+}
+
+fn position_of<T: PartialEq>(values: &[T], value: T) -> usize {
+    values.iter().position(|v| *v == value).unwrap_or(0)
+}
+
+fn ms_names(values: &[u32]) -> Vec<CString> {
+    values
+        .iter()
+        .map(|ms| CString::new(format!("{ms} ms")).unwrap())
+        .collect()
+}
+
+fn as_c_strs(names: &[CString]) -> Vec<&CStr> {
+    names.iter().map(|name| name.as_c_str()).collect()
+}
@@ -0,0 +1,49 @@
+use alloc::{ffi::CString, format};
+
+use flipperzero::gui::{canvas::CanvasView, view_port::InputEvent};
+use flipperzero_sys as sys;
+
+use crate::{
+    bt::Bt,
+    hid::HidSink,
+    menu,
+    screen::{App, Screen, ScreenId, Transition},
+};
+
+/// Asks before forgetting pairing with active host, other hosts have their
+/// keys stored elsewhere and stay paired
+pub struct UnpairScreen;
+
+impl UnpairScreen {
+    pub const ID: ScreenId = "unpair";
+}
+
+impl Screen for UnpairScreen {
+    fn draw(&self, app: &App, canvas: &mut CanvasView<'_>) {
+        let host = format!("{}?", app.settings.active_host().name);
+        canvas.draw_str(2, 30, c"Unpair from");
+        canvas.draw_str(2, 40, CString::new(host).unwrap_or_default());
+        canvas.draw_str(2, 110, c"Ok: yes");
+        canvas.draw_str(2, 120, c"Back: no");
+    }
+
+    fn on_input(
+        &mut self,
+        _app: &mut App,
+        event: &InputEvent,
+        _hid: &dyn HidSink,
+    ) -> Transition {
+        match menu::confirm(event.key, event.type_) {
+            Some(true) => {
+                let bt = Bt::open();
+                bt.disconnect();
+                unsafe { sys::furi_delay_ms(200) };
+                bt.forget_bonded_devices();
+                Bt::start_advertising();
+                Transition::Pop
+            }
+            Some(false) => Transition::Pop,
+            None => Transition::Stay,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use core::ffi::CStr;
+
+use alloc::{ffi::CString, format, string::String, vec::Vec};
+
+#[cfg(target_os = "none")]
+use crate::{
+    Error,
+    storage::{APP_DATA_DIR, Storage},
+};
+use crate::{
+    hid::HidSink,
+    typing::{Layout, TypeError, type_text},
+};
+
+/// Path of recent queries, one per line, newest first
+pub const HISTORY_PATH: &CStr = c"/ext/apps_data/yt_remote/search_history.txt";
+
+/// How many queries are remembered
+pub const HISTORY_LEN: usize = 8;
+
+/// Longest query that can be entered
+pub const MAX_QUERY_LEN: usize = 64;
+
+/// Recent search queries, newest first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    queries: Vec<CString>,
+}
+
+impl History {
+    pub fn parse(text: &str) -> Self {
+        let mut history = Self::default();
+        for line in text.lines().rev() {
+            history.push(line);
+        }
+        history
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for query in &self.queries {
+            text.push_str(&query.to_string_lossy());
+            text.push('\n');
+        }
+        text
+    }
+
+    #[cfg(target_os = "none")]
+    pub fn load(storage: &Storage) -> Self {
+        storage
+            .read_to_string(HISTORY_PATH)
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    #[cfg(target_os = "none")]
+    pub fn save(&self, storage: &Storage) -> Result<(), Error> {
+        storage.mkdir(APP_DATA_DIR)?;
+        storage.write(HISTORY_PATH, self.to_text().as_bytes())
+    }
+
+    pub fn queries(&self) -> &[CString] {
+        &self.queries
+    }
+
+    /// Moves `query` to the front, dropping oldest ones past
+    /// [`HISTORY_LEN`]. Blank queries are ignored.
+    pub fn push(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        let Ok(query) = CString::new(query) else {
+            return;
+        };
+        self.queries.retain(|q| *q != query);
+        self.queries.insert(0, query);
+        self.queries.truncate(HISTORY_LEN);
+    }
+}
+
+/// Searches YouTube for `query`: focuses search box with `/`, types query,
+/// and submits it with Enter.
+///
+/// See [`type_text`] for `pace`.
+pub fn search(
+    hid: &impl HidSink,
+    layout: &dyn Layout,
+    query: &str,
+    pace: impl FnMut() -> bool,
+) -> Result<(), TypeError> {
+    type_text(hid, layout, &format!("/{query}\n"), pace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bt::{Key, KeyCombo, KeyMods},
+        hid::{HidEvent, RecordingHid},
+        typing::Us,
+    };
+
+    fn queries(history: &History) -> Vec<&str> {
+        history
+            .queries()
+            .iter()
+            .map(|q| q.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn repeated_query_moves_to_front() {
+        let mut history = History::default();
+        history.push("cats");
+        history.push("dogs");
+        history.push(" cats ");
+        assert_eq!(queries(&history), ["cats", "dogs"]);
+    }
+
+    #[test]
+    fn keeps_newest_queries() {
+        let mut history = History::default();
+        for i in 0..HISTORY_LEN + 2 {
+            history.push(&format!("query {i}"));
+        }
+        assert_eq!(history.queries().len(), HISTORY_LEN);
+        let newest = format!("query {}", HISTORY_LEN + 1);
+        assert_eq!(queries(&history)[0], newest);
+        assert_eq!(queries(&history)[HISTORY_LEN - 1], "query 2");
+    }
+
+    #[test]
+    fn blank_queries_are_ignored() {
+        let mut history = History::default();
+        history.push("");
+        history.push(" \t ");
+        history.push("a\0b");
+        assert_eq!(history, History::default());
+        assert_eq!(History::parse("\n  \ncats\n"), {
+            let mut history = History::default();
+            history.push("cats");
+            history
+        });
+    }
+
+    #[test]
+    fn text_keeps_order() {
+        let history = History::parse("newest\nmiddle\noldest\n");
+        assert_eq!(queries(&history), ["newest", "middle", "oldest"]);
+        assert_eq!(history.to_text(), "newest\nmiddle\noldest\n");
+        assert_eq!(History::parse(&history.to_text()), history);
+    }
+
+    #[test]
+    fn search_focuses_box_then_submits() {
+        let hid = RecordingHid::new();
+        search(&hid, &Us, "Hi", || true).unwrap();
+        let taps: Vec<HidEvent> = [
+            KeyCombo::from(Key::Slash),
+            KeyCombo::new(Key::H, KeyMods::LeftShift),
+            Key::I.into(),
+            Key::Enter.into(),
+        ]
+        .into_iter()
+        .flat_map(|combo| {
+            [HidEvent::KeyPress(combo), HidEvent::KeyRelease(combo)]
+        })
+        .collect();
+        assert_eq!(hid.take(), taps);
+    }
+}
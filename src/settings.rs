@@ -0,0 +1,719 @@
+//! User settings, changed from settings screen and kept on SD card
+
+use core::{array, ffi::CStr, ops::RangeInclusive};
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[cfg(target_os = "none")]
+use flipperzero::println;
+
+#[cfg(target_os = "none")]
+use crate::{
+    Error,
+    storage::{APP_DATA_DIR, Storage},
+};
+use crate::{
+    bt::HidProfileParams,
+    hosts::{HOST_SLOTS, Host},
+    mouse::{AccelCurve, MotionConfig},
+    pattern::PatternConfig,
+    repeat::RepeatConfig,
+    typing::{LAYOUTS, Layout, Us, layout_from_name},
+    view_port::Orientation,
+};
+
+/// Path of saved settings, see [`Settings::parse`] for format
+pub const SETTINGS_PATH: &CStr = c"/ext/apps_data/yt_remote/settings.txt";
+
+/// Where settings file that couldn't be read is moved, so that it's not
+/// lost once settings are saved again
+pub const SETTINGS_BACKUP_PATH: &CStr =
+    c"/ext/apps_data/yt_remote/settings.bak";
+
+/// Version of settings file written by this app. Files without version
+/// header are version 1.
+pub const SETTINGS_VERSION: u32 = 3;
+
+/// Keys renamed since version 1: version they were renamed in, old name and
+/// new name
+const RENAMED_KEYS: &[(u32, &str, &str)] = &[
+    // single host became first of host slots
+    (3, "mac_xor", "host1_mac_xor"),
+];
+
+/// Highest [`Settings::mouse_speed`]
+pub const MAX_MOUSE_SPEED: u8 = 5;
+
+/// Names of settings values, as shown on screen and written to file
+pub const ACCEL_CURVES: &[(AccelCurve, &CStr)] = &[
+    (AccelCurve::Linear, c"Linear"),
+    (AccelCurve::Quadratic, c"Quadratic"),
+    (AccelCurve::Exponential, c"Exponential"),
+];
+
+pub const REPEAT_RATES: &[(RepeatRate, &CStr)] = &[
+    (RepeatRate::Slow, c"Slow"),
+    (RepeatRate::Normal, c"Normal"),
+    (RepeatRate::Fast, c"Fast"),
+];
+
+pub const ORIENTATIONS: &[(Orientation, &CStr)] = &[
+    (Orientation::Vertical, c"Vertical"),
+    (Orientation::VerticalFlip, c"VerticalFlip"),
+    (Orientation::Horizontal, c"Horizontal"),
+    (Orientation::HorizontalFlip, c"HorizontalFlip"),
+];
+
+/// How fast held inputs repeat, see [`RepeatConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatRate {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl RepeatRate {
+    pub fn config(self) -> RepeatConfig {
+        match self {
+            RepeatRate::Slow => RepeatConfig {
+                delay_ms: 1000,
+                interval_ms: 600,
+                accel_after_ms: Some(3000),
+                fast_interval_ms: 400,
+            },
+            RepeatRate::Normal => RepeatConfig::default(),
+            RepeatRate::Fast => RepeatConfig {
+                delay_ms: 500,
+                interval_ms: 250,
+                accel_after_ms: Some(1500),
+                fast_interval_ms: 150,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Name of profile active at startup, `None` for custom keymap if there
+    /// is one, or first builtin profile
+    pub profile: Option<String>,
+    /// Cursor speed, from 1 to [`MAX_MOUSE_SPEED`]
+    pub mouse_speed: u8,
+    pub accel_curve: AccelCurve,
+    pub repeat_rate: RepeatRate,
+    pub orientation: Orientation,
+    /// Vibrate on button press
+    pub haptic: bool,
+    /// Blink LED on button press
+    pub led: bool,
+    /// Keyboard layout host is set to, name from [`LAYOUTS`]
+    pub layout: &'static str,
+    /// Timeouts of input patterns, see [`PatternConfig`]
+    pub tap_gap_ms: u32,
+    pub chord_window_ms: u32,
+    pub very_long_ms: u32,
+    /// Name host sees, applied by restarting HID profile, see
+    /// [`HidProfileParams::is_valid_device_name`]
+    pub device_name: String,
+    /// Index of active host, connected to again on next start
+    pub host: usize,
+    pub hosts: [Host; HOST_SLOTS],
+    /// Lines of settings file this version doesn't know or can't read,
+    /// likely written by newer one, kept so that they survive saving
+    pub unknown: Vec<String>,
+    /// Version settings file is written with, newer than
+    /// [`SETTINGS_VERSION`] if it was read from newer app's file
+    pub version: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let patterns = PatternConfig::default();
+        Self {
+            profile: None,
+            mouse_speed: 3,
+            accel_curve: AccelCurve::Linear,
+            repeat_rate: RepeatRate::Normal,
+            orientation: Orientation::VerticalFlip,
+            haptic: false,
+            led: false,
+            layout: "US",
+            tap_gap_ms: patterns.tap_gap_ms,
+            chord_window_ms: patterns.chord_window_ms,
+            very_long_ms: patterns.very_long_ms,
+            device_name: "YtRemote".into(),
+            host: 0,
+            hosts: array::from_fn(Host::new),
+            unknown: Vec::new(),
+            version: SETTINGS_VERSION,
+        }
+    }
+}
+
+impl Settings {
+    /// Parses settings file, version header followed by one `key = value`
+    /// per line:
+    /// ```text
+    /// # comment
+    /// version = 3
+    /// profile = YouTube
+    /// mouse_speed = 3
+    /// accel_curve = Linear
+    /// repeat_rate = Normal
+    /// orientation = VerticalFlip
+    /// haptic = on
+    /// led = off
+    /// layout = US
+    /// tap_gap_ms = 300
+    /// chord_window_ms = 150
+    /// very_long_ms = 2000
+    /// device_name = YtRemote
+    /// host = 1
+    /// host1 = TV
+    /// host1_mac_xor = 1
+    /// host2 = Laptop
+    /// host2_mac_xor = 2
+    /// ```
+    /// Settings not mentioned keep their default value. Keys from older
+    /// versions are migrated to current ones. Lines with unknown keys or
+    /// values that can't be read are kept in [`Settings::unknown`], with
+    /// setting keeping its default value.
+    ///
+    /// Files of newer versions are read the same, without migrating any
+    /// keys, and keep their version when written back, so that newer app
+    /// doesn't migrate them again.
+    ///
+    /// A misplaced or invalid `version` line is an error; a file without one
+    /// is read as version 1.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut settings = Self::default();
+        let mut version = None;
+        for (i, line) in text.lines().enumerate() {
+            let error = |kind| ParseError { line: i + 1, kind };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                version.get_or_insert(1);
+                settings.keep(line.into());
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if key == "version" {
+                if version.is_some() {
+                    return Err(error(ParseErrorKind::MisplacedVersion));
+                }
+                let parsed: u32 = value
+                    .parse()
+                    .ok()
+                    .filter(|&v| v > 0)
+                    .ok_or(error(ParseErrorKind::InvalidVersion))?;
+                // newer file keeps its version, older ones get migrated
+                settings.version = parsed.max(SETTINGS_VERSION);
+                version = Some(parsed);
+                continue;
+            }
+            // version has to come before any setting
+            let version = *version.get_or_insert(1);
+            let key = migrate_key(version, key);
+            match settings.set(key, value) {
+                Ok(()) => settings.unknown.retain(|l| line_key(l) != key),
+                Err(_) => settings.keep(format!("{key} = {value}")),
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Keeps unreadable `line`, replacing earlier one with the same key
+    fn keep(&mut self, line: String) {
+        self.unknown.retain(|l| line_key(l) != line_key(&line));
+        self.unknown.push(line);
+    }
+
+    /// Parses settings file, which might not be valid UTF-8 if it got
+    /// corrupted
+    pub fn decode(bytes: &[u8]) -> Result<Self, ParseError> {
+        match core::str::from_utf8(bytes) {
+            Ok(text) => Self::parse(text),
+            Err(err) => {
+                let valid = &bytes[..err.valid_up_to()];
+                let line = valid.iter().filter(|&&b| b == b'\n').count() + 1;
+                Err(ParseError {
+                    line,
+                    kind: ParseErrorKind::InvalidUtf8,
+                })
+            }
+        }
+    }
+
+    /// Sets setting named `key` from its textual `value`
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParseErrorKind> {
+        let invalid = ParseErrorKind::InvalidValue;
+        match key {
+            "profile" => {
+                self.profile = (!value.is_empty()).then(|| value.into())
+            }
+            "mouse_speed" => {
+                self.mouse_speed = value
+                    .parse()
+                    .ok()
+                    .filter(|speed| (1..=MAX_MOUSE_SPEED).contains(speed))
+                    .ok_or(invalid)?
+            }
+            "accel_curve" => {
+                self.accel_curve =
+                    from_name(ACCEL_CURVES, value).ok_or(invalid)?
+            }
+            "repeat_rate" => {
+                self.repeat_rate =
+                    from_name(REPEAT_RATES, value).ok_or(invalid)?
+            }
+            "orientation" => {
+                self.orientation =
+                    from_name(ORIENTATIONS, value).ok_or(invalid)?
+            }
+            "haptic" => self.haptic = parse_bool(value).ok_or(invalid)?,
+            "led" => self.led = parse_bool(value).ok_or(invalid)?,
+            "layout" => {
+                self.layout = LAYOUTS
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(value))
+                    .map(|&(name, _)| name)
+                    .ok_or(invalid)?
+            }
+            "tap_gap_ms" => {
+                self.tap_gap_ms = parse_in(value, 100..=1000).ok_or(invalid)?
+            }
+            "chord_window_ms" => {
+                self.chord_window_ms =
+                    parse_in(value, 50..=500).ok_or(invalid)?
+            }
+            "very_long_ms" => {
+                self.very_long_ms =
+                    parse_in(value, 1000..=10_000).ok_or(invalid)?
+            }
+            "device_name" if HidProfileParams::is_valid_device_name(value) => {
+                self.device_name = value.into()
+            }
+            "device_name" => return Err(invalid),
+            "host" => {
+                let host = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|host| (1..=HOST_SLOTS).contains(host))
+                    .ok_or(invalid)?;
+                self.host = host - 1;
+            }
+            key => match host_key(key) {
+                Some((i, "")) if Host::is_valid_name(value) => {
+                    self.hosts[i].name = value.into()
+                }
+                Some((_, "")) => return Err(invalid),
+                // 0 would collide with Flipper's default profile
+                Some((i, "_mac_xor")) => {
+                    self.hosts[i].mac_xor = value
+                        .parse()
+                        .ok()
+                        .filter(|&xor| xor != 0)
+                        .ok_or(invalid)?
+                }
+                _ => return Err(ParseErrorKind::UnknownKey),
+            },
+        }
+        Ok(())
+    }
+
+    /// Writes settings in format [`Settings::parse`] reads.
+    ///
+    /// Kept line of known setting that's still at its default value is
+    /// written instead of it, so that value newer app wrote survives.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("version = {}\n", self.version);
+        let defaults = Self::default().lines();
+        let lines = self.lines();
+        for (key, value) in &lines {
+            let unchanged = defaults.contains(&(key.clone(), value.clone()));
+            let kept = self.unknown.iter().find(|l| line_key(l) == key);
+            match kept {
+                Some(line) if unchanged => text.push_str(line),
+                _ => text.push_str(&format!("{key} = {value}")),
+            }
+            text.push('\n');
+        }
+        for line in &self.unknown {
+            let key = line_key(line);
+            if !lines.iter().any(|(k, _)| k == key)
+                && !defaults.iter().any(|(k, _)| k == key)
+            {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Key and value of every setting, as written to file
+    fn lines(&self) -> Vec<(String, String)> {
+        let mut lines = Vec::new();
+        if let Some(profile) = &self.profile {
+            lines.push(("profile".into(), profile.clone()));
+        }
+        let settings = [
+            ("mouse_speed", self.mouse_speed.to_string()),
+            ("accel_curve", name(ACCEL_CURVES, self.accel_curve).into()),
+            ("repeat_rate", name(REPEAT_RATES, self.repeat_rate).into()),
+            ("orientation", name(ORIENTATIONS, self.orientation).into()),
+            ("haptic", bool_name(self.haptic).into()),
+            ("led", bool_name(self.led).into()),
+            ("layout", self.layout.into()),
+            ("tap_gap_ms", self.tap_gap_ms.to_string()),
+            ("chord_window_ms", self.chord_window_ms.to_string()),
+            ("very_long_ms", self.very_long_ms.to_string()),
+            ("device_name", self.device_name.clone()),
+            ("host", (self.host + 1).to_string()),
+        ];
+        lines.extend(settings.map(|(key, value)| (key.into(), value)));
+        for (i, host) in self.hosts.iter().enumerate() {
+            let n = i + 1;
+            lines.push((format!("host{n}"), host.name.clone()));
+            lines.push((format!("host{n}_mac_xor"), host.mac_xor.to_string()));
+        }
+        lines
+    }
+
+    /// Loads saved settings, falling back to defaults if there are none.
+    ///
+    /// Settings file that can't be parsed is moved to
+    /// [`SETTINGS_BACKUP_PATH`], and defaults are used instead.
+    #[cfg(target_os = "none")]
+    pub fn load(storage: &Storage) -> Self {
+        let Ok(bytes) = storage.read(SETTINGS_PATH) else {
+            return Self::default();
+        };
+        Self::decode(&bytes).unwrap_or_else(|err| {
+            println!("settings.txt:{}: {:?}\r", err.line, err.kind);
+            let _ = storage.write(SETTINGS_BACKUP_PATH, &bytes);
+            let _ = storage.remove(SETTINGS_PATH);
+            Self::default()
+        })
+    }
+
+    #[cfg(target_os = "none")]
+    pub fn save(&self, storage: &Storage) -> Result<(), Error> {
+        storage.mkdir(APP_DATA_DIR)?;
+        storage.write(SETTINGS_PATH, self.to_text().as_bytes())
+    }
+
+    /// Cursor motion for Mouse mode
+    pub fn motion_config(&self) -> MotionConfig {
+        let default = MotionConfig::default();
+        let scale = self.mouse_speed as f32 / 3.0;
+        let gain = match self.accel_curve {
+            AccelCurve::Linear => default.gain,
+            AccelCurve::Quadratic => 0.05,
+            AccelCurve::Exponential => 0.1,
+        };
+        MotionConfig {
+            curve: self.accel_curve,
+            base_speed: default.base_speed * scale,
+            gain: gain * scale,
+            max_speed: default.max_speed * scale,
+        }
+    }
+
+    pub fn repeat_config(&self) -> RepeatConfig {
+        self.repeat_rate.config()
+    }
+
+    pub fn pattern_config(&self) -> PatternConfig {
+        PatternConfig {
+            tap_gap_ms: self.tap_gap_ms,
+            chord_window_ms: self.chord_window_ms,
+            very_long_ms: self.very_long_ms,
+        }
+    }
+
+    /// Layout text is typed with
+    pub fn layout(&self) -> &'static dyn Layout {
+        layout_from_name(self.layout).unwrap_or(&Us)
+    }
+
+    /// Host currently connected to, or advertised to
+    pub fn active_host(&self) -> &Host {
+        &self.hosts[self.host]
+    }
+}
+
+/// Name of `value` in `names`
+pub fn name<T: PartialEq>(
+    names: &[(T, &'static CStr)],
+    value: T,
+) -> &'static str {
+    names
+        .iter()
+        .find(|(v, _)| *v == value)
+        .and_then(|(_, name)| name.to_str().ok())
+        .unwrap_or_default()
+}
+
+/// Value called `name` in `names`, ignoring case
+pub fn from_name<T: Copy>(names: &[(T, &CStr)], name: &str) -> Option<T> {
+    names
+        .iter()
+        .find(|(_, n)| n.to_str().is_ok_and(|n| n.eq_ignore_ascii_case(name)))
+        .map(|(value, _)| *value)
+}
+
+/// Current name of `key` found in file of given `version`
+fn migrate_key(version: u32, key: &str) -> &str {
+    RENAMED_KEYS
+        .iter()
+        .filter(|(renamed_in, ..)| version < *renamed_in)
+        .fold(
+            key,
+            |key, (_, old, new)| if key == *old { new } else { key },
+        )
+}
+
+/// Key of `key = value` line, whole line if it has no value
+fn line_key(line: &str) -> &str {
+    line.split_once('=').map_or(line, |(key, _)| key).trim()
+}
+
+/// Splits `host<N><field>` key into index of host and field, `host<N>`
+/// being host's name
+fn host_key(key: &str) -> Option<(usize, &str)> {
+    let key = key.strip_prefix("host")?;
+    let digits = key.find(|c: char| !c.is_ascii_digit()).unwrap_or(key.len());
+    let (number, field) = key.split_at(digits);
+    let index = number.parse::<usize>().ok()?.checked_sub(1)?;
+    (index < HOST_SLOTS).then_some((index, field))
+}
+
+/// Number within `range`
+fn parse_in(value: &str, range: RangeInclusive<u32>) -> Option<u32> {
+    value.parse().ok().filter(|n| range.contains(n))
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        _ if value.eq_ignore_ascii_case("on") => Some(true),
+        _ if value.eq_ignore_ascii_case("off") => Some(false),
+        _ => None,
+    }
+}
+
+fn bool_name(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// Only used internally, lines with unknown keys are kept instead
+    UnknownKey,
+    /// Only used internally, lines with invalid values are kept instead
+    InvalidValue,
+    InvalidVersion,
+    /// Version header came after settings, or more than once
+    MisplacedVersion,
+    InvalidUtf8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(
+        line: usize,
+        kind: ParseErrorKind,
+    ) -> Result<Settings, ParseError> {
+        Err(ParseError { line, kind })
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut settings = Settings {
+            profile: Some("Music".into()),
+            mouse_speed: 5,
+            accel_curve: AccelCurve::Exponential,
+            repeat_rate: RepeatRate::Fast,
+            orientation: Orientation::Horizontal,
+            haptic: true,
+            led: true,
+            layout: "FR",
+            tap_gap_ms: 250,
+            chord_window_ms: 200,
+            very_long_ms: 5000,
+            device_name: "Remote".into(),
+            host: 1,
+            unknown: vec!["future = 1".into()],
+            ..Settings::default()
+        };
+        settings.hosts[1].name = "Laptop".into();
+        settings.hosts[1].mac_xor = 7;
+        let text = settings.to_text();
+        assert!(text.starts_with("version = 3\n"));
+        assert!(text.ends_with("future = 1\n"));
+        assert_eq!(Settings::parse(&text), Ok(settings));
+        let defaults = Settings::default();
+        assert_eq!(Settings::parse(&defaults.to_text()), Ok(defaults));
+    }
+
+    #[test]
+    fn comments_and_blank_lines() {
+        let text = "# mine\n\nversion = 3 # current\n  led = on  # blink\n";
+        assert!(Settings::parse(text).unwrap().led);
+    }
+
+    #[test]
+    fn migrates_v1() {
+        // no version header
+        let settings = Settings::parse("haptic = on\nmac_xor = 5").unwrap();
+        assert!(settings.haptic);
+        assert_eq!(settings.hosts[0].mac_xor, 5);
+        assert_eq!(settings.unknown, Vec::<String>::new());
+        let text = settings.to_text();
+        assert!(text.starts_with("version = 3\n"));
+        assert!(text.contains("\nhost1_mac_xor = 5\n"));
+        assert!(!text.contains("\nmac_xor"));
+        let v2 = Settings::parse("version = 2\nmac_xor = 5").unwrap();
+        assert_eq!(v2.hosts[0].mac_xor, 5);
+    }
+
+    #[test]
+    fn current_keys_arent_migrated() {
+        let settings = Settings::parse("version = 3\nmac_xor = 5").unwrap();
+        assert_eq!(
+            settings.hosts[0].mac_xor,
+            Settings::default().hosts[0].mac_xor
+        );
+        assert_eq!(settings.unknown, ["mac_xor = 5"]);
+    }
+
+    #[test]
+    fn version_errors() {
+        use ParseErrorKind::*;
+        assert_eq!(
+            Settings::parse("led = on\nversion = 3"),
+            error(2, MisplacedVersion)
+        );
+        assert_eq!(
+            Settings::parse("version = 3\nversion = 3"),
+            error(2, MisplacedVersion)
+        );
+        assert_eq!(
+            Settings::parse("# x\nversion = three"),
+            error(2, InvalidVersion)
+        );
+        assert_eq!(Settings::parse("version = 0"), error(1, InvalidVersion));
+    }
+
+    #[test]
+    fn keeps_unknown_keys() {
+        let text =
+            "version = 3\nfuture = 1 # note\nshiny\nfuture = 2\nled = on";
+        let settings = Settings::parse(text).unwrap();
+        assert!(settings.led);
+        assert_eq!(settings.unknown, ["shiny", "future = 2"]);
+        assert!(settings.to_text().ends_with("\nshiny\nfuture = 2\n"));
+    }
+
+    #[test]
+    fn invalid_value_keeps_default_and_line() {
+        let text = "mouse_speed = 9\nled = on";
+        let mut settings = Settings::parse(text).unwrap();
+        assert_eq!(settings.mouse_speed, 3);
+        assert!(settings.led);
+        assert_eq!(settings.unknown, ["mouse_speed = 9"]);
+        let written = settings.to_text();
+        assert!(written.contains("\nmouse_speed = 9\n"));
+        assert!(!written.contains("mouse_speed = 3"));
+        assert_eq!(Settings::parse(&written).as_ref(), Ok(&settings));
+        // once changed here, own value wins
+        settings.mouse_speed = 2;
+        let written = settings.to_text();
+        assert!(written.contains("\nmouse_speed = 2\n"));
+        assert!(!written.contains("mouse_speed = 9"));
+    }
+
+    #[test]
+    fn mac_xor_isnt_zero() {
+        let settings = Settings::parse("host2_mac_xor = 0").unwrap();
+        assert_eq!(settings.hosts[1].mac_xor, 2);
+        assert_eq!(settings.unknown, ["host2_mac_xor = 0"]);
+        let settings = Settings::parse("host2_mac_xor = 65535").unwrap();
+        assert_eq!(settings.hosts[1].mac_xor, 65535);
+    }
+
+    #[test]
+    fn valid_value_replaces_kept_line() {
+        let settings =
+            Settings::parse("mouse_speed = 9\nmouse_speed = 3").unwrap();
+        assert_eq!(settings.unknown, Vec::<String>::new());
+    }
+
+    #[test]
+    fn newer_version_is_kept() {
+        let text = "version = 4\nled = on\nglow = 3";
+        let settings = Settings::parse(text).unwrap();
+        assert!(settings.led);
+        assert_eq!(settings.version, 4);
+        assert_eq!(settings.unknown, ["glow = 3"]);
+        let written = settings.to_text();
+        assert!(written.starts_with("version = 4\n"));
+        assert!(written.ends_with("\nglow = 3\n"));
+    }
+
+    #[test]
+    fn decode() {
+        assert!(Settings::decode(b"led = on").unwrap().led);
+        assert_eq!(
+            Settings::decode(b"version = 3\nled = on\ndevice_name = \xffx"),
+            error(3, ParseErrorKind::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn pattern_timeouts() {
+        let text =
+            "tap_gap_ms = 400\nchord_window_ms = 100\nvery_long_ms = 3000";
+        let settings = Settings::parse(text).unwrap();
+        assert_eq!(
+            settings.pattern_config(),
+            PatternConfig {
+                tap_gap_ms: 400,
+                chord_window_ms: 100,
+                very_long_ms: 3000,
+            }
+        );
+        let defaults = Settings::default();
+        assert_eq!(defaults.pattern_config(), PatternConfig::default());
+        let settings = Settings::parse("tap_gap_ms = 50").unwrap();
+        assert_eq!(settings.tap_gap_ms, defaults.tap_gap_ms);
+        assert_eq!(settings.unknown, ["tap_gap_ms = 50"]);
+    }
+
+    #[test]
+    fn layout_by_name() {
+        let settings = Settings::parse("layout = de").unwrap();
+        assert_eq!(settings.layout, "DE");
+        let z = settings.layout().keystroke('z');
+        assert_eq!(z, layout_from_name("DE").unwrap().keystroke('z'));
+        assert!(settings.to_text().contains("\nlayout = DE\n"));
+        let unknown = Settings::parse("layout = Dvorak").unwrap();
+        assert_eq!(unknown.layout, "US");
+        assert_eq!(unknown.unknown, ["layout = Dvorak"]);
+    }
+}
@@ -0,0 +1,117 @@
+use core::ffi::CStr;
+
+use alloc::{string::String, vec::Vec};
+use flipperzero_sys::{self as sys, furi::UnsafeRecord};
+
+use crate::Error;
+
+pub const RECORD_STORAGE: &CStr = c"storage";
+
+/// Where app keeps its config files
+pub const APP_DATA_DIR: &CStr = c"/ext/apps_data/yt_remote";
+
+pub struct Storage {
+    hnd: UnsafeRecord<sys::Storage>,
+}
+
+impl Storage {
+    pub fn open() -> Self {
+        let hnd = unsafe { UnsafeRecord::open(RECORD_STORAGE) };
+        Self { hnd }
+    }
+
+    pub fn as_ptr(&self) -> *mut sys::Storage {
+        self.hnd.as_ptr()
+    }
+
+    pub fn read(&self, path: &CStr) -> Result<Vec<u8>, Error> {
+        let file =
+            File::open(self, path, sys::FSAM_READ, sys::FSOM_OPEN_EXISTING)?;
+        let mut res = Vec::new();
+        let mut buf = [0; 64];
+        loop {
+            let read = unsafe {
+                sys::storage_file_read(
+                    file.as_ptr(),
+                    buf.as_mut_ptr().cast(),
+                    buf.len(),
+                )
+            };
+            if read == 0 {
+                break;
+            }
+            res.extend_from_slice(&buf[..read]);
+        }
+        Ok(res)
+    }
+
+    pub fn read_to_string(&self, path: &CStr) -> Result<String, Error> {
+        String::from_utf8(self.read(path)?).map_err(|_| Error)
+    }
+
+    /// Replaces contents of file at `path`, creating it if needed
+    pub fn write(&self, path: &CStr, data: &[u8]) -> Result<(), Error> {
+        let file =
+            File::open(self, path, sys::FSAM_WRITE, sys::FSOM_CREATE_ALWAYS)?;
+        let written = unsafe {
+            sys::storage_file_write(
+                file.as_ptr(),
+                data.as_ptr().cast(),
+                data.len(),
+            )
+        };
+        (written == data.len()).then_some(()).ok_or(Error)
+    }
+
+    /// Creates directory, succeeding if it already exists
+    pub fn mkdir(&self, path: &CStr) -> Result<(), Error> {
+        let res =
+            unsafe { sys::storage_simply_mkdir(self.as_ptr(), path.as_ptr()) };
+        res.then_some(()).ok_or(Error)
+    }
+
+    /// Removes file or empty directory, succeeding if it doesn't exist
+    pub fn remove(&self, path: &CStr) -> Result<(), Error> {
+        let res =
+            unsafe { sys::storage_simply_remove(self.as_ptr(), path.as_ptr()) };
+        res.then_some(()).ok_or(Error)
+    }
+}
+
+struct File {
+    hnd: *mut sys::File,
+}
+
+impl File {
+    fn open(
+        storage: &Storage,
+        path: &CStr,
+        access_mode: sys::FS_AccessMode,
+        open_mode: sys::FS_OpenMode,
+    ) -> Result<Self, Error> {
+        let hnd = unsafe { sys::storage_file_alloc(storage.as_ptr()) };
+        let file = Self { hnd };
+        let res = unsafe {
+            sys::storage_file_open(
+                file.as_ptr(),
+                path.as_ptr(),
+                access_mode,
+                open_mode,
+            )
+        };
+        res.then_some(file).ok_or(Error)
+    }
+
+    fn as_ptr(&self) -> *mut sys::File {
+        self.hnd
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        unsafe {
+            sys::storage_file_close(self.as_ptr());
+            sys::storage_file_free(self.as_ptr());
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use crate::{
+    Error,
+    bt::{Key, KeyCombo, KeyMods},
+    hid::HidSink,
+};
+
+/// AltGr, as used by European layouts
+const ALT_GR: KeyMods = KeyMods::RightAlt;
+
+/// How to type single character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keystroke {
+    pub combo: KeyCombo,
+    /// Combo is a dead key, which only produces its character when followed
+    /// by space
+    pub dead: bool,
+}
+
+impl Keystroke {
+    pub const fn new(key: Key, mods: KeyMods) -> Self {
+        Self {
+            combo: KeyCombo::new(key, mods),
+            dead: false,
+        }
+    }
+
+    const fn dead(self) -> Self {
+        Self { dead: true, ..self }
+    }
+}
+
+const fn plain(key: Key) -> Keystroke {
+    Keystroke::new(key, KeyMods::empty())
+}
+
+const fn shift(key: Key) -> Keystroke {
+    Keystroke::new(key, KeyMods::LeftShift)
+}
+
+const fn alt_gr(key: Key) -> Keystroke {
+    Keystroke::new(key, ALT_GR)
+}
+
+/// Key labeled with ASCII letter `c` on US keyboard
+fn letter(c: char) -> Key {
+    Key::from_code(Key::A.code() + (c.to_ascii_lowercase() as u8 - b'a'))
+}
+
+/// Key labeled with ASCII digit `c` on US keyboard
+fn digit(c: char) -> Key {
+    match c {
+        '0' => Key::Num0,
+        _ => Key::from_code(Key::Num1.code() + (c as u8 - b'1')),
+    }
+}
+
+/// Whitespace, same on every layout
+fn whitespace(c: char) -> Option<Keystroke> {
+    let key = match c {
+        ' ' => Key::Spacebar,
+        '\n' => Key::Enter,
+        '\t' => Key::Tab,
+        _ => return None,
+    };
+    Some(plain(key))
+}
+
+/// Keyboard layout host is set to, which decides what keys produce which
+/// characters
+pub trait Layout {
+    /// How to type `c`, `None` if layout has no key for it
+    fn keystroke(&self, c: char) -> Option<Keystroke>;
+}
+
+/// All builtin layouts, by name
+pub const LAYOUTS: &[(&str, &dyn Layout)] =
+    &[("US", &Us), ("UK", &Uk), ("DE", &De), ("FR", &Fr)];
+
+/// Looks up builtin layout by name, ignoring ASCII case
+pub fn layout_from_name(name: &str) -> Option<&'static dyn Layout> {
+    LAYOUTS
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|&(_, layout)| layout)
+}
+
+/// US QWERTY
+pub struct Us;
+
+impl Layout for Us {
+    fn keystroke(&self, c: char) -> Option<Keystroke> {
+        let stroke = match c {
+            'a'..='z' => plain(letter(c)),
+            'A'..='Z' => shift(letter(c)),
+            '0'..='9' => plain(digit(c)),
+            '!' => shift(Key::Num1),
+            '@' => shift(Key::Num2),
+            '#' => shift(Key::Num3),
+            '$' => shift(Key::Num4),
+            '%' => shift(Key::Num5),
+            '^' => shift(Key::Num6),
+            '&' => shift(Key::Num7),
+            '*' => shift(Key::Num8),
+            '(' => shift(Key::Num9),
+            ')' => shift(Key::Num0),
+            '-' => plain(Key::Minus),
+            '_' => shift(Key::Minus),
+            '=' => plain(Key::Equal),
+            '+' => shift(Key::Equal),
+            '[' => plain(Key::LeftBracket),
+            '{' => shift(Key::LeftBracket),
+            ']' => plain(Key::RightBracket),
+            '}' => shift(Key::RightBracket),
+            '\\' => plain(Key::RightSlash),
+            '|' => shift(Key::RightSlash),
+            ';' => plain(Key::Semicolon),
+            ':' => shift(Key::Semicolon),
+            '\'' => plain(Key::Apostrophe),
+            '"' => shift(Key::Apostrophe),
+            '`' => plain(Key::Grave),
+            '~' => shift(Key::Grave),
+            ',' => plain(Key::Comma),
+            '<' => shift(Key::Comma),
+            '.' => plain(Key::Dot),
+            '>' => shift(Key::Dot),
+            '/' => plain(Key::Slash),
+            '?' => shift(Key::Slash),
+            _ => return whitespace(c),
+        };
+        Some(stroke)
+    }
+}
+
+/// UK QWERTY, differs from US only in few symbols
+pub struct Uk;
+
+impl Layout for Uk {
+    fn keystroke(&self, c: char) -> Option<Keystroke> {
+        let stroke = match c {
+            '"' => shift(Key::Num2),
+            '£' => shift(Key::Num3),
+            '@' => shift(Key::Apostrophe),
+            '#' => plain(Key::Hash),
+            '~' => shift(Key::Hash),
+            '\\' => plain(Key::NonUsBackslash),
+            '|' => shift(Key::NonUsBackslash),
+            '¬' => shift(Key::Grave),
+            _ => return Us.keystroke(c),
+        };
+        Some(stroke)
+    }
+}
+
+/// German QWERTZ
+pub struct De;
+
+impl Layout for De {
+    fn keystroke(&self, c: char) -> Option<Keystroke> {
+        let stroke = match c {
+            'y' => plain(Key::Z),
+            'Y' => shift(Key::Z),
+            'z' => plain(Key::Y),
+            'Z' => shift(Key::Y),
+            'a'..='z' => plain(letter(c)),
+            'A'..='Z' => shift(letter(c)),
+            '0'..='9' => plain(digit(c)),
+            '!' => shift(Key::Num1),
+            '"' => shift(Key::Num2),
+            '§' => shift(Key::Num3),
+            '$' => shift(Key::Num4),
+            '%' => shift(Key::Num5),
+            '&' => shift(Key::Num6),
+            '/' => shift(Key::Num7),
+            '(' => shift(Key::Num8),
+            ')' => shift(Key::Num9),
+            '=' => shift(Key::Num0),
+            '{' => alt_gr(Key::Num7),
+            '[' => alt_gr(Key::Num8),
+            ']' => alt_gr(Key::Num9),
+            '}' => alt_gr(Key::Num0),
+            'ß' => plain(Key::Minus),
+            '?' => shift(Key::Minus),
+            '\\' => alt_gr(Key::Minus),
+            '´' => plain(Key::Equal).dead(),
+            '`' => shift(Key::Equal).dead(),
+            'ü' => plain(Key::LeftBracket),
+            'Ü' => shift(Key::LeftBracket),
+            '+' => plain(Key::RightBracket),
+            '*' => shift(Key::RightBracket),
+            '~' => alt_gr(Key::RightBracket),
+            '#' => plain(Key::Hash),
+            '\'' => shift(Key::Hash),
+            'ö' => plain(Key::Semicolon),
+            'Ö' => shift(Key::Semicolon),
+            'ä' => plain(Key::Apostrophe),
+            'Ä' => shift(Key::Apostrophe),
+            '^' => plain(Key::Grave).dead(),
+            '°' => shift(Key::Grave),
+            ',' => plain(Key::Comma),
+            ';' => shift(Key::Comma),
+            '.' => plain(Key::Dot),
+            ':' => shift(Key::Dot),
+            '-' => plain(Key::Slash),
+            '_' => shift(Key::Slash),
+            '<' => plain(Key::NonUsBackslash),
+            '>' => shift(Key::NonUsBackslash),
+            '|' => alt_gr(Key::NonUsBackslash),
+            '@' => alt_gr(Key::Q),
+            '€' => alt_gr(Key::E),
+            _ => return whitespace(c),
+        };
+        Some(stroke)
+    }
+}
+
+/// French AZERTY
+pub struct Fr;
+
+impl Layout for Fr {
+    fn keystroke(&self, c: char) -> Option<Keystroke> {
+        let stroke = match c {
+            'a' => plain(Key::Q),
+            'A' => shift(Key::Q),
+            'q' => plain(Key::A),
+            'Q' => shift(Key::A),
+            'z' => plain(Key::W),
+            'Z' => shift(Key::W),
+            'w' => plain(Key::Z),
+            'W' => shift(Key::Z),
+            'm' => plain(Key::Semicolon),
+            'M' => shift(Key::Semicolon),
+            'a'..='z' => plain(letter(c)),
+            'A'..='Z' => shift(letter(c)),
+            // digits need shift
+            '0'..='9' => shift(digit(c)),
+            '&' => plain(Key::Num1),
+            'é' => plain(Key::Num2),
+            '~' => alt_gr(Key::Num2).dead(),
+            '"' => plain(Key::Num3),
+            '#' => alt_gr(Key::Num3),
+            '\'' => plain(Key::Num4),
+            '{' => alt_gr(Key::Num4),
+            '(' => plain(Key::Num5),
+            '[' => alt_gr(Key::Num5),
+            '-' => plain(Key::Num6),
+            '|' => alt_gr(Key::Num6),
+            'è' => plain(Key::Num7),
+            '`' => alt_gr(Key::Num7).dead(),
+            '_' => plain(Key::Num8),
+            '\\' => alt_gr(Key::Num8),
+            'ç' => plain(Key::Num9),
+            '^' => alt_gr(Key::Num9),
+            'à' => plain(Key::Num0),
+            '@' => alt_gr(Key::Num0),
+            ')' => plain(Key::Minus),
+            '°' => shift(Key::Minus),
+            ']' => alt_gr(Key::Minus),
+            '=' => plain(Key::Equal),
+            '+' => shift(Key::Equal),
+            '}' => alt_gr(Key::Equal),
+            '$' => plain(Key::RightBracket),
+            '£' => shift(Key::RightBracket),
+            'ù' => plain(Key::Apostrophe),
+            '%' => shift(Key::Apostrophe),
+            '*' => plain(Key::Hash),
+            'µ' => shift(Key::Hash),
+            ',' => plain(Key::M),
+            '?' => shift(Key::M),
+            ';' => plain(Key::Comma),
+            '.' => shift(Key::Comma),
+            ':' => plain(Key::Dot),
+            '/' => shift(Key::Dot),
+            '!' => plain(Key::Slash),
+            '§' => shift(Key::Slash),
+            '<' => plain(Key::NonUsBackslash),
+            '>' => shift(Key::NonUsBackslash),
+            '²' => plain(Key::Grave),
+            _ => return whitespace(c),
+        };
+        Some(stroke)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeError {
+    /// Layout has no key for this character, nothing was typed
+    Unmappable(char),
+    /// Stopped before typing whole text
+    Cancelled,
+    Hid,
+}
+
+impl From<Error> for TypeError {
+    fn from(_: Error) -> Self {
+        TypeError::Hid
+    }
+}
+
+/// Types `text` into host, as if it had `layout` set.
+///
+/// `pace` is called after every key report, and should wait for however
+/// long host needs between them. Returning `false` from it cancels typing,
+/// releasing held key first.
+///
+/// Text is checked up front, so that unmappable characters don't leave it
+/// half-typed.
+pub fn type_text(
+    hid: &impl HidSink,
+    layout: &dyn Layout,
+    text: &str,
+    mut pace: impl FnMut() -> bool,
+) -> Result<(), TypeError> {
+    if let Some(c) = text.chars().find(|&c| layout.keystroke(c).is_none()) {
+        return Err(TypeError::Unmappable(c));
+    }
+    let mut tap = |combo: KeyCombo| {
+        hid.key_press(combo)?;
+        if !pace() {
+            hid.key_release(combo)?;
+            return Err(TypeError::Cancelled);
+        }
+        hid.key_release(combo)?;
+        if !pace() {
+            return Err(TypeError::Cancelled);
+        }
+        Ok(())
+    };
+    for stroke in text.chars().filter_map(|c| layout.keystroke(c)) {
+        tap(stroke.combo)?;
+        if stroke.dead {
+            tap(Key::Spacebar.into())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::{HidEvent, RecordingHid};
+    use alloc::vec::Vec;
+
+    fn typed(layout: &dyn Layout, text: &str) -> Vec<HidEvent> {
+        let hid = RecordingHid::new();
+        type_text(&hid, layout, text, || true).unwrap();
+        hid.take()
+    }
+
+    /// Events of tapping every stroke in turn
+    fn taps(strokes: &[Keystroke]) -> Vec<HidEvent> {
+        strokes
+            .iter()
+            .flat_map(|s| {
+                [HidEvent::KeyPress(s.combo), HidEvent::KeyRelease(s.combo)]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn us_text() {
+        assert_eq!(
+            typed(&Us, "Hi!\n"),
+            taps(&[
+                shift(Key::H),
+                plain(Key::I),
+                shift(Key::Num1),
+                plain(Key::Enter),
+            ])
+        );
+    }
+
+    #[test]
+    fn uk_differs_in_symbols_only() {
+        assert_eq!(
+            typed(&Uk, "@a"),
+            taps(&[shift(Key::Apostrophe), plain(Key::A)])
+        );
+        assert_eq!(typed(&Uk, "£"), taps(&[shift(Key::Num3)]));
+    }
+
+    #[test]
+    fn de_swaps_y_and_z() {
+        assert_eq!(typed(&De, "yZ"), taps(&[plain(Key::Z), shift(Key::Y)]));
+        assert_eq!(typed(&De, "ß"), taps(&[plain(Key::Minus)]));
+    }
+
+    #[test]
+    fn fr_shifts_digits() {
+        assert_eq!(
+            typed(&Fr, "1é0"),
+            taps(&[shift(Key::Num1), plain(Key::Num2), shift(Key::Num0)])
+        );
+        assert_eq!(
+            typed(&Fr, "aqzwm"),
+            taps(&[
+                plain(Key::Q),
+                plain(Key::A),
+                plain(Key::W),
+                plain(Key::Z),
+                plain(Key::Semicolon),
+            ])
+        );
+    }
+
+    #[test]
+    fn dead_keys_are_followed_by_space() {
+        let space = plain(Key::Spacebar);
+        assert_eq!(
+            typed(&De, "^a"),
+            taps(&[plain(Key::Grave), space, plain(Key::A)])
+        );
+        assert_eq!(typed(&Fr, "~"), taps(&[alt_gr(Key::Num2), space]));
+        assert!(!De.keystroke('°').unwrap().dead);
+    }
+
+    #[test]
+    fn unmappable_text_types_nothing() {
+        let hid = RecordingHid::new();
+        assert_eq!(
+            type_text(&hid, &Us, "ab€", || true),
+            Err(TypeError::Unmappable('€'))
+        );
+        assert_eq!(hid.take(), []);
+    }
+
+    /// Types `text`, with pacing cancelling on `cancel_at`th call
+    fn cancelled(
+        layout: &dyn Layout,
+        text: &str,
+        cancel_at: usize,
+    ) -> Vec<HidEvent> {
+        let hid = RecordingHid::new();
+        let mut calls = 0;
+        let result = type_text(&hid, layout, text, || {
+            calls += 1;
+            calls < cancel_at
+        });
+        assert_eq!(result, Err(TypeError::Cancelled));
+        assert_eq!(calls, cancel_at);
+        hid.take()
+    }
+
+    #[test]
+    fn cancel_while_key_held_releases_it() {
+        assert_eq!(
+            cancelled(&Us, "abc", 3),
+            taps(&[plain(Key::A), plain(Key::B)])
+        );
+    }
+
+    #[test]
+    fn cancel_between_keys_stops_typing() {
+        assert_eq!(cancelled(&Us, "abc", 2), taps(&[plain(Key::A)]));
+    }
+
+    #[test]
+    fn cancel_before_dead_key_space() {
+        assert_eq!(cancelled(&De, "^a", 2), taps(&[plain(Key::Grave)]));
+    }
+
+    #[test]
+    fn layout_names_ignore_case() {
+        let de = layout_from_name("de").unwrap();
+        assert_eq!(de.keystroke('z'), Some(plain(Key::Y)));
+        assert!(layout_from_name("XX").is_none());
+    }
+}
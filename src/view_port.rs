@@ -0,0 +1,49 @@
+//! Input, orientation and canvas types of firmware's view port. Firmware
+//! isn't there on host, so tests get stand-ins with the same variants.
+
+#[cfg(target_os = "none")]
+pub use flipperzero::gui::{
+    canvas::CanvasView,
+    view_port::{InputEvent, InputKey, InputType, Orientation},
+};
+
+#[cfg(not(target_os = "none"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKey {
+    Up,
+    Down,
+    Right,
+    Left,
+    Ok,
+    Back,
+}
+
+#[cfg(not(target_os = "none"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputType {
+    Press,
+    Release,
+    Short,
+    Long,
+    Repeat,
+}
+
+#[cfg(not(target_os = "none"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Horizontal,
+    HorizontalFlip,
+    Vertical,
+    VerticalFlip,
+}
+
+#[cfg(not(target_os = "none"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub key: InputKey,
+    pub type_: InputType,
+}
+
+/// Nothing to draw on, on host
+#[cfg(not(target_os = "none"))]
+pub struct CanvasView<'a>(core::marker::PhantomData<&'a mut ()>);
@@ -0,0 +1,176 @@
+//! Firmware's stock views, shown on top of app's view port until user is done
+//! with them
+
+use core::{
+    ffi::{CStr, c_void},
+    ptr::null_mut,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use alloc::{string::String, vec};
+use flipperzero_sys::{self as sys, furi::UnsafeRecord};
+
+pub const RECORD_GUI: &CStr = c"gui";
+
+/// No answer from view yet
+const PENDING: u32 = u32::MAX;
+/// Back was pressed
+const CANCELLED: u32 = u32::MAX - 1;
+
+/// Shows `view`, blocking until one of its callbacks stores answer in
+/// `answer`, or until Back is pressed, which gives `None`
+fn run(view: *mut sys::View, answer: &AtomicU32) -> Option<u32> {
+    let gui = unsafe { UnsafeRecord::<sys::Gui>::open(RECORD_GUI) };
+    let context = answer as *const AtomicU32 as *mut c_void;
+    answer.store(PENDING, Ordering::Relaxed);
+    unsafe {
+        let holder = sys::view_holder_alloc();
+        sys::view_holder_set_view(holder, view);
+        sys::view_holder_set_back_callback(holder, Some(on_back), context);
+        sys::view_holder_attach_to_gui(holder, gui.as_ptr());
+        sys::view_holder_start(holder);
+        while answer.load(Ordering::Relaxed) == PENDING {
+            sys::furi_delay_ms(10);
+        }
+        sys::view_holder_stop(holder);
+        sys::view_holder_set_view(holder, null_mut());
+        sys::view_holder_free(holder);
+    }
+    match answer.load(Ordering::Relaxed) {
+        CANCELLED => None,
+        answer => Some(answer),
+    }
+}
+
+unsafe extern "C" fn on_back(context: *mut c_void) {
+    let answer = unsafe { &*context.cast::<AtomicU32>() };
+    answer.store(CANCELLED, Ordering::Relaxed);
+}
+
+unsafe extern "C" fn on_text_entered(context: *mut c_void) {
+    let answer = unsafe { &*context.cast::<AtomicU32>() };
+    answer.store(0, Ordering::Relaxed);
+}
+
+unsafe extern "C" fn on_item_selected(context: *mut c_void, index: u32) {
+    let answer = unsafe { &*context.cast::<AtomicU32>() };
+    answer.store(index, Ordering::Relaxed);
+}
+
+/// Asks for line of text, up to `max_len` bytes long. Gives `None` if user
+/// backed out, or entered nothing.
+pub fn text_input(header: &CStr, max_len: usize) -> Option<String> {
+    let answer = AtomicU32::new(PENDING);
+    let context = &answer as *const AtomicU32 as *mut c_void;
+    let mut buf = vec![0u8; max_len + 1];
+    let entered = unsafe {
+        let text_input = sys::text_input_alloc();
+        sys::text_input_set_header_text(text_input, header.as_ptr());
+        sys::text_input_set_result_callback(
+            text_input,
+            Some(on_text_entered),
+            context,
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+            true,
+        );
+        let entered = run(sys::text_input_get_view(text_input), &answer);
+        sys::text_input_free(text_input);
+        entered
+    };
+    entered?;
+    let text = CStr::from_bytes_until_nul(&buf).ok()?.to_str().ok()?;
+    (!text.is_empty()).then(|| text.into())
+}
+
+/// Lets user pick one of `items`, giving its index, or `None` if user
+/// backed out
+pub fn submenu(header: &CStr, items: &[&CStr]) -> Option<usize> {
+    let answer = AtomicU32::new(PENDING);
+    let context = &answer as *const AtomicU32 as *mut c_void;
+    unsafe {
+        let submenu = sys::submenu_alloc();
+        sys::submenu_set_header(submenu, header.as_ptr());
+        for (i, item) in items.iter().enumerate() {
+            sys::submenu_add_item(
+                submenu,
+                item.as_ptr(),
+                i as u32,
+                Some(on_item_selected),
+                context,
+            );
+        }
+        let selected = run(sys::submenu_get_view(submenu), &answer);
+        sys::submenu_free(submenu);
+        selected.map(|i| i as usize)
+    }
+}
+
+/// Item of [`variable_item_list`], with one of `values` picked
+pub struct ListItem<'a> {
+    pub label: &'a CStr,
+    pub values: &'a [&'a CStr],
+    /// Index of picked value
+    pub index: usize,
+}
+
+/// Shows `items`, letting user pick their values with Left/Right, starting
+/// with cursor on `selected` item. Gives index of item Ok was pressed on,
+/// or `None` if user backed out. Either way, `items` keep values user
+/// picked.
+pub fn variable_item_list(
+    items: &mut [ListItem],
+    selected: usize,
+) -> Option<usize> {
+    let answer = AtomicU32::new(PENDING);
+    let context = &answer as *const AtomicU32 as *mut c_void;
+    unsafe {
+        let list = sys::variable_item_list_alloc();
+        for i in 0..items.len() {
+            let item = items.as_mut_ptr().add(i);
+            let count = (*item).values.len().min(u8::MAX as usize) as u8;
+            let index = (*item).index.min(count.saturating_sub(1) as usize);
+            let variable_item = sys::variable_item_list_add(
+                list,
+                (*item).label.as_ptr(),
+                count,
+                Some(on_value_changed),
+                item.cast(),
+            );
+            if let Some(value) = (*item).values.get(index) {
+                sys::variable_item_set_current_value_index(
+                    variable_item,
+                    index as u8,
+                );
+                sys::variable_item_set_current_value_text(
+                    variable_item,
+                    value.as_ptr(),
+                );
+            }
+        }
+        sys::variable_item_list_set_enter_callback(
+            list,
+            Some(on_item_selected),
+            context,
+        );
+        sys::variable_item_list_set_selected_item(list, selected as u8);
+        let entered = run(sys::variable_item_list_get_view(list), &answer);
+        sys::variable_item_list_free(list);
+        entered.map(|i| i as usize)
+    }
+}
+
+unsafe extern "C" fn on_value_changed(variable_item: *mut sys::VariableItem) {
+    unsafe {
+        let item = &mut *sys::variable_item_get_context(variable_item)
+            .cast::<ListItem>();
+        item.index =
+            sys::variable_item_get_current_value_index(variable_item) as usize;
+        if let Some(value) = item.values.get(item.index) {
+            sys::variable_item_set_current_value_text(
+                variable_item,
+                value.as_ptr(),
+            );
+        }
+    }
+}